@@ -0,0 +1,366 @@
+// Computer-controlled ships. They fly and shoot through the same code as the player's
+// ship, so they burn fuel, overheat their guns and lose hull the same way.
+use crate::DisplayQuality;
+use crate::combat::damage::{LastDamage, ShieldRecharge};
+use crate::combat::{Bounty, CombatSet};
+use crate::economy::Wallet;
+use crate::game_over::spawn_explosion;
+use crate::pause::PauseState;
+use crate::physics::Velocity;
+use crate::physics::collision::{Collider, CollisionResponse};
+use crate::radar::RadarSignature;
+use crate::session::ShipSnapshot;
+use crate::ship::control::{ControlSet, PlayerControlled, ShipControlIntent};
+use crate::ship::movement::{FlightModel, RetroThrust};
+use crate::ship::spaceship::{MAX_FUEL, Spaceship};
+use crate::ship::weapons::{PROJECTILE_SPEED, Weapon};
+use crate::space::OnSpaceScreen;
+use bevy::prelude::*;
+use rand::Rng;
+
+pub mod steering;
+
+use steering::{arrive, avoid, controls_towards, flee, seek};
+
+pub fn ai_plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        (
+            update_ai_state,
+            pilot_ships
+                .after(update_ai_state)
+                .in_set(ControlSet::Intent),
+            destroy_npcs.after(CombatSet::ApplyDamage),
+        )
+            .run_if(in_state(PauseState::Running)),
+    );
+}
+
+// Hostile ships spotted closer than this are chased, and given up past the second range
+const DETECTION_RANGE: f32 = 3000.0;
+const LOSE_RANGE: f32 = 4500.0;
+// Pilots open fire inside the attack range and only go back to chasing past the exit range
+const ATTACK_RANGE: f32 = 900.0;
+const ATTACK_EXIT_RANGE: f32 = 1200.0;
+// Distance attackers try to keep from their target
+const STANDOFF: f32 = 500.0;
+// Cosine of the largest angle between the nose and the target the pilot fires at
+const AIM_TOLERANCE: f32 = 0.97;
+const AIM_GAIN: f32 = 3.0;
+// Pilots break off below this hull, and head home below this much fuel
+const FLEE_HULL: f32 = 0.3;
+const LOW_FUEL: f32 = 20.0;
+// Patrols circle this far from the center of their home
+const PATROL_RADIUS: f32 = 1100.0;
+const PATROL_POINT_REACHED: f32 = 150.0;
+// A ship this close to its home's surface can be resupplied
+const HOME_RANGE: f32 = 300.0;
+const HOME_STANDOFF: f32 = 150.0;
+// Refill rates while resting at home, per second
+const RESUPPLY_FUEL: f32 = 30.0;
+const RESUPPLY_HULL: f32 = 0.1;
+const SLOWING_RADIUS: f32 = 400.0;
+const PATROL_SPEED: f32 = 250.0;
+const PURSUIT_SPEED: f32 = 500.0;
+const FLEE_SPEED: f32 = 700.0;
+// How far ahead pursuers aim at where their target will be, in seconds
+const PURSUIT_LEAD: f32 = 0.5;
+const PIRATE_COUNT: usize = 3;
+const PIRATE_BOUNTY: u32 = 150;
+// Pirates fire slower than the player's gun, so that a fight against the whole patrol
+// lasts long enough to shoot back or run: about 18 s of hits from a single pirate wreck
+// a ship with full shields
+const PIRATE_FIRE_INTERVAL: f32 = 0.5;
+const PIRATE_SIZE: f32 = 96.0;
+const PIRATE_SPRITE_WIDTH: f32 = 137.0;
+const PIRATE_EXPLOSION_SPEED: f32 = 250.0;
+
+// What a computer pilot is busy with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AiState {
+    // Circling around home, looking for prey
+    Patrol,
+    // Chasing a hostile ship
+    Pursue(Entity),
+    // Close enough to shoot at a hostile ship
+    Attack(Entity),
+    // Running away from a hostile ship with a damaged hull
+    Flee(Entity),
+    // Heading home for fuel and repairs
+    ReturnToBase,
+}
+
+// Tag component for a ship flown by the computer, with the pilot's state of mind
+#[derive(Component, Debug)]
+pub struct AiPilot {
+    pub state: AiState,
+    // Station the pilot patrols around and goes back to for fuel and repairs
+    pub home: Entity,
+    // Patrol point currently flown to, relative to home
+    patrol_point: Vec2,
+}
+
+impl AiPilot {
+    pub fn new(home: Entity) -> Self {
+        Self {
+            state: AiState::Patrol,
+            home,
+            patrol_point: random_patrol_point(),
+        }
+    }
+}
+
+fn random_patrol_point() -> Vec2 {
+    Vec2::from_angle(rand::rng().random_range(0.0..std::f32::consts::TAU)) * PATROL_RADIUS
+}
+
+// Decides the pilot's next state from its ship, the nearest hostile ship with its
+// distance, and whether the ship is resting at home
+pub fn next_state(
+    state: AiState,
+    ship: &Spaceship,
+    hostile: Option<(Entity, f32)>,
+    at_home: bool,
+) -> AiState {
+    let in_sight = |range: f32| hostile.filter(|(_, distance)| *distance < range);
+    match state {
+        AiState::ReturnToBase if at_home && ship.fuel >= MAX_FUEL && ship.hull >= 1.0 => {
+            AiState::Patrol
+        }
+        AiState::ReturnToBase => AiState::ReturnToBase,
+        _ if ship.hull < FLEE_HULL => match in_sight(LOSE_RANGE) {
+            Some((threat, _)) => AiState::Flee(threat),
+            None => AiState::ReturnToBase,
+        },
+        _ if ship.fuel < LOW_FUEL => AiState::ReturnToBase,
+        AiState::Patrol | AiState::Flee(_) => match in_sight(DETECTION_RANGE) {
+            Some((target, _)) => AiState::Pursue(target),
+            None => AiState::Patrol,
+        },
+        AiState::Pursue(_) | AiState::Attack(_) => match in_sight(LOSE_RANGE) {
+            Some((target, distance)) if distance < ATTACK_RANGE => AiState::Attack(target),
+            Some((target, distance))
+                if distance < ATTACK_EXIT_RANGE && matches!(state, AiState::Attack(_)) =>
+            {
+                AiState::Attack(target)
+            }
+            Some((target, _)) => AiState::Pursue(target),
+            None => AiState::Patrol,
+        },
+    }
+}
+
+pub fn spawn_pirate(
+    commands: &mut Commands,
+    asset_server: &Res<AssetServer>,
+    home: Entity,
+    snapshot: ShipSnapshot,
+    shield_regen_delay: f32,
+) -> Entity {
+    commands
+        .spawn((
+            Sprite {
+                image: asset_server.load("F5S1.png"),
+                ..default()
+            },
+            snapshot.transform,
+            snapshot.stats,
+            snapshot.velocity,
+            AiPilot::new(home),
+            ShipControlIntent::default(),
+            FlightModel::Arcade,
+            RetroThrust(true),
+            Weapon::with_interval(PIRATE_FIRE_INTERVAL),
+            ShieldRecharge::new(shield_regen_delay),
+            Collider::aabb(0.8),
+            Bounty(PIRATE_BOUNTY),
+            RadarSignature::Hostile,
+            OnSpaceScreen,
+        ))
+        .id()
+}
+
+// Pirates of a new game, spread along their patrol around `home_position`
+pub fn pirate_patrol(home_position: Vec2) -> Vec<ShipSnapshot> {
+    (0..PIRATE_COUNT)
+        .map(|i| {
+            let angle = i as f32 * std::f32::consts::TAU / PIRATE_COUNT as f32;
+            let position = home_position + Vec2::from_angle(angle) * PATROL_RADIUS;
+            ShipSnapshot {
+                stats: Spaceship {
+                    throttle: 0.0,
+                    fuel: MAX_FUEL,
+                    hull: 1.0,
+                    shields: 1.0,
+                    weapons: 1,
+                },
+                transform: Transform::from_translation(position.extend(0.0))
+                    .with_scale(Vec3::splat(PIRATE_SIZE / PIRATE_SPRITE_WIDTH)),
+                velocity: Velocity::default(),
+            }
+        })
+        .collect()
+}
+
+fn update_ai_state(
+    mut pilots: Query<(&mut AiPilot, &mut Spaceship, &Transform)>,
+    hostiles: Query<(Entity, &Transform), With<PlayerControlled>>,
+    homes: Query<(&Transform, Option<&Collider>), Without<AiPilot>>,
+    time: Res<Time>,
+) {
+    let dt = time.delta_secs();
+    for (mut pilot, mut ship, transform) in &mut pilots {
+        let position = transform.translation.truncate();
+        let hostile = hostiles
+            .iter()
+            .map(|(entity, hostile)| {
+                let distance = hostile.translation.truncate().distance(position);
+                (entity, distance)
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+        let at_home = homes.get(pilot.home).is_ok_and(|(home, collider)| {
+            let radius = collider.map(Collider::radius).unwrap_or_default();
+            home.translation.truncate().distance(position) - radius < HOME_RANGE
+        });
+        pilot.state = next_state(pilot.state, &ship, hostile, at_home);
+
+        // Resting at home tops up the tank and patches the hull
+        if pilot.state == AiState::ReturnToBase && at_home {
+            ship.fuel = (ship.fuel + RESUPPLY_FUEL * dt).min(MAX_FUEL);
+            ship.hull = (ship.hull + RESUPPLY_HULL * dt).min(1.0);
+        }
+    }
+}
+
+// Computer pilots steer and shoot through the same intent as the player
+#[allow(clippy::type_complexity)]
+fn pilot_ships(
+    mut pilots: Query<(&mut AiPilot, &Transform, &Velocity, &mut ShipControlIntent)>,
+    others: Query<(&Transform, Option<&Velocity>, Option<&Collider>), Without<AiPilot>>,
+    obstacles: Query<(&Transform, &Collider), (With<CollisionResponse>, Without<AiPilot>)>,
+) {
+    for (mut pilot, transform, velocity, mut intent) in &mut pilots {
+        let position = transform.translation.truncate();
+        let forward = (transform.rotation * Vec3::Y).truncate();
+        let locate = |entity: Entity| {
+            others
+                .get(entity)
+                .ok()
+                .map(|(transform, velocity, collider)| {
+                    (
+                        transform.translation.truncate(),
+                        velocity.map(|v| v.0).unwrap_or_default(),
+                        collider.map(Collider::radius).unwrap_or_default(),
+                    )
+                })
+        };
+
+        let desired = match pilot.state {
+            AiState::Patrol => match locate(pilot.home) {
+                Some((home, home_velocity, _)) => {
+                    let point = home + pilot.patrol_point;
+                    if position.distance(point) < PATROL_POINT_REACHED {
+                        pilot.patrol_point = random_patrol_point();
+                    }
+                    home_velocity + arrive(position, point, PATROL_SPEED, SLOWING_RADIUS)
+                }
+                None => Vec2::ZERO,
+            },
+            AiState::Pursue(target) => match locate(target) {
+                Some((target, target_velocity, _)) => seek(
+                    position,
+                    target + target_velocity * PURSUIT_LEAD,
+                    PURSUIT_SPEED,
+                ),
+                None => Vec2::ZERO,
+            },
+            AiState::Attack(target) => match locate(target) {
+                Some((target, target_velocity, _)) => {
+                    let away = (position - target).normalize_or_zero();
+                    target_velocity
+                        + arrive(
+                            position,
+                            target + away * STANDOFF,
+                            PURSUIT_SPEED,
+                            SLOWING_RADIUS,
+                        )
+                }
+                None => Vec2::ZERO,
+            },
+            AiState::Flee(threat) => match locate(threat) {
+                Some((threat, _, _)) => flee(position, threat, FLEE_SPEED),
+                None => Vec2::ZERO,
+            },
+            AiState::ReturnToBase => match locate(pilot.home) {
+                Some((home, home_velocity, radius)) => {
+                    let side = (position - home).normalize_or_zero();
+                    let stop = home + side * (radius + HOME_STANDOFF);
+                    home_velocity + arrive(position, stop, PURSUIT_SPEED, SLOWING_RADIUS)
+                }
+                None => Vec2::ZERO,
+            },
+        };
+        let desired = desired
+            + avoid(
+                position,
+                velocity.0,
+                obstacles.iter().map(|(transform, collider)| {
+                    (transform.translation.truncate(), collider.radius())
+                }),
+            );
+        let (mut turn, thrust) = controls_towards(forward, velocity.0, desired);
+        let mut fire = false;
+
+        // While attacking, the nose points at where the target will be when the shot
+        // gets there rather than where the ship is going
+        if let AiState::Attack(target) = pilot.state
+            && let Some((target, target_velocity, _)) = locate(target)
+        {
+            let flight_time = position.distance(target) / PROJECTILE_SPEED;
+            let aim = target + (target_velocity - velocity.0) * flight_time;
+            let aim_direction = (aim - position).normalize_or_zero();
+            turn = (forward.angle_to(aim_direction) * AIM_GAIN).clamp(-1.0, 1.0);
+            fire = forward.dot(aim_direction) > AIM_TOLERANCE;
+        }
+
+        *intent = ShipControlIntent { turn, thrust, fire };
+    }
+}
+
+// Computer ships are gone as soon as their hull is, paying out if the player got them
+#[allow(clippy::type_complexity)]
+fn destroy_npcs(
+    mut commands: Commands,
+    npcs: Query<
+        (
+            Entity,
+            &Spaceship,
+            &Transform,
+            Option<&Bounty>,
+            Option<&LastDamage>,
+        ),
+        With<AiPilot>,
+    >,
+    players: Query<(), With<PlayerControlled>>,
+    mut wallet: ResMut<Wallet>,
+    display_quality: Res<DisplayQuality>,
+) {
+    for (entity, ship, transform, bounty, last_damage) in &npcs {
+        if ship.hull > 0.0 {
+            continue;
+        }
+        let killed_by_player = last_damage
+            .and_then(|damage| damage.source)
+            .is_some_and(|source| players.contains(source));
+        if let (Some(bounty), true) = (bounty, killed_by_player) {
+            wallet.earn(bounty.0);
+        }
+        spawn_explosion(
+            &mut commands,
+            transform.translation,
+            PIRATE_EXPLOSION_SPEED,
+            *display_quality,
+        );
+        commands.entity(entity).despawn();
+    }
+}
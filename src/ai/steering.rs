@@ -0,0 +1,89 @@
+// Steering behaviours. Each one returns the velocity the pilot would like to have, and
+// `controls_towards` turns that wish into the same stick inputs a player would give.
+use bevy::prelude::*;
+
+// Obstacles further ahead than this many seconds of flight are ignored
+const AVOID_LOOK_AHEAD: f32 = 2.5;
+// Clearance kept from an obstacle's surface
+const AVOID_MARGIN: f32 = 250.0;
+// How sharply the ship turns towards the wanted heading, full stick past 1/TURN_GAIN radians
+const TURN_GAIN: f32 = 3.0;
+// Heading must be this well aligned (cosine of the angle) before the engines push
+const THRUST_ALIGNMENT: f32 = 0.8;
+// Speed difference tolerated before throttling up or down
+const SPEED_TOLERANCE: f32 = 20.0;
+
+// Full speed straight at the target
+pub fn seek(position: Vec2, target: Vec2, max_speed: f32) -> Vec2 {
+    (target - position).normalize_or_zero() * max_speed
+}
+
+// Full speed straight away from the threat
+pub fn flee(position: Vec2, threat: Vec2, max_speed: f32) -> Vec2 {
+    -seek(position, threat, max_speed)
+}
+
+// Like `seek`, but slowing down inside `slowing_radius` so as to stop on the target
+pub fn arrive(position: Vec2, target: Vec2, max_speed: f32, slowing_radius: f32) -> Vec2 {
+    let offset = target - position;
+    let distance = offset.length();
+    if distance < f32::EPSILON {
+        return Vec2::ZERO;
+    }
+    let speed = max_speed * (distance / slowing_radius).min(1.0);
+    offset / distance * speed
+}
+
+// Push away from obstacles, given as center and radius, that lie on the ship's path over
+// the next few seconds. Returns zero when the way is clear.
+pub fn avoid(
+    position: Vec2,
+    velocity: Vec2,
+    obstacles: impl IntoIterator<Item = (Vec2, f32)>,
+) -> Vec2 {
+    let speed = velocity.length();
+    let Some(heading) = velocity.try_normalize() else {
+        return Vec2::ZERO;
+    };
+    let look_ahead = speed * AVOID_LOOK_AHEAD;
+    let mut push = Vec2::ZERO;
+    for (center, radius) in obstacles {
+        let offset = center - position;
+        // How far along the path the obstacle is, and how far off to the side
+        let along = offset.dot(heading);
+        if along < 0.0 || along > look_ahead + radius {
+            continue;
+        }
+        let side = offset - heading * along;
+        let clearance = radius + AVOID_MARGIN;
+        let miss = side.length();
+        if miss >= clearance {
+            continue;
+        }
+        // Veer to the side the obstacle is not on, harder the closer it is
+        let away = (-side).try_normalize().unwrap_or(heading.perp());
+        let urgency = 1.0 - along / (look_ahead + radius);
+        push += away * speed * (1.0 - miss / clearance).max(urgency);
+    }
+    push
+}
+
+// Stick inputs (turn, thrust) bringing a ship pointing along `forward` and moving at
+// `velocity` towards the `desired` velocity, as written in a `ShipControlIntent`
+pub fn controls_towards(forward: Vec2, velocity: Vec2, desired: Vec2) -> (f32, f32) {
+    let Some(heading) = desired.try_normalize() else {
+        // Nowhere to go: keep the heading and bleed off speed
+        return (0.0, -1.0);
+    };
+    let turn = (forward.angle_to(heading) * TURN_GAIN).clamp(-1.0, 1.0);
+    let speed = velocity.dot(forward);
+    let wanted = desired.length();
+    let thrust = if forward.dot(heading) < THRUST_ALIGNMENT || speed > wanted + SPEED_TOLERANCE {
+        -1.0
+    } else if speed < wanted - SPEED_TOLERANCE {
+        1.0
+    } else {
+        0.0
+    };
+    (turn, thrust)
+}
@@ -0,0 +1,250 @@
+// Music for each game state and sound effects. Music and effects have their own volume
+// in the sound settings, both scaled by the master volume.
+use crate::pause::PauseState;
+use crate::ship::control::{PlayerControlled, ShipControlIntent};
+use crate::ship::spaceship::{MAX_FUEL, Spaceship};
+use crate::space::{OnSpaceScreen, SunDamageWarning};
+use crate::{GameState, MusicVolume, SfxVolume, Volume};
+use bevy::asset::LoadState;
+use bevy::audio::Volume as Gain;
+use bevy::prelude::*;
+
+// Below this fraction of a full tank the low fuel alarm sounds
+const LOW_FUEL_FRACTION: f32 = 0.2;
+// Seconds between two low fuel alarms
+const LOW_FUEL_ALARM_INTERVAL: f32 = 4.0;
+// Highest level of each volume setting
+const MAX_VOLUME: f32 = 9.0;
+
+pub fn audio_plugin(app: &mut App) {
+    app.add_systems(Startup, load_sounds)
+        .add_systems(
+            Update,
+            (
+                play_state_music.run_if(state_changed::<GameState>),
+                update_music_volume
+                    .run_if(resource_changed::<Volume>.or(resource_changed::<MusicVolume>)),
+                update_sfx_volume
+                    .run_if(resource_changed::<Volume>.or(resource_changed::<SfxVolume>)),
+                button_click_sound,
+            ),
+        )
+        .add_systems(
+            Update,
+            (thrust_sound, low_fuel_alarm, sun_warning_sound).run_if(in_state(PauseState::Running)),
+        )
+        .add_systems(OnExit(PauseState::Running), stop_thrust_sound);
+}
+
+#[derive(Resource)]
+struct Sounds {
+    menu_theme: Handle<AudioSource>,
+    space_ambience: Handle<AudioSource>,
+    docked_music: Handle<AudioSource>,
+    thrust: Handle<AudioSource>,
+    low_fuel: Handle<AudioSource>,
+    sun_warning: Handle<AudioSource>,
+    click: Handle<AudioSource>,
+}
+
+// Tag component for the music currently playing
+#[derive(Component)]
+struct Music;
+
+// Tag component for the engine sound, playing while the player thrusts
+#[derive(Component)]
+struct ThrustSound;
+
+fn load_sounds(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(Sounds {
+        menu_theme: asset_server.load("audio/menu_theme.ogg"),
+        space_ambience: asset_server.load("audio/space_ambience.ogg"),
+        docked_music: asset_server.load("audio/docked_music.ogg"),
+        thrust: asset_server.load("audio/thrust.ogg"),
+        low_fuel: asset_server.load("audio/low_fuel.ogg"),
+        sun_warning: asset_server.load("audio/sun_warning.ogg"),
+        click: asset_server.load("audio/click.ogg"),
+    });
+}
+
+// Playback volume of a channel set to `level`, scaled by the master volume
+fn gain(master: Volume, level: u32) -> Gain {
+    Gain::Linear(master.0 as f32 / MAX_VOLUME * level as f32 / MAX_VOLUME)
+}
+
+// Plays `sound` once on the effects channel. A sound whose file failed to load is skipped:
+// its player would never start, so it would never despawn either.
+fn play_sfx(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    sound: &Handle<AudioSource>,
+    volume: Volume,
+    sfx: SfxVolume,
+) {
+    if matches!(asset_server.load_state(sound), LoadState::Failed(_)) {
+        return;
+    }
+    commands.spawn((
+        AudioPlayer(sound.clone()),
+        PlaybackSettings::DESPAWN.with_volume(gain(volume, sfx.0)),
+    ));
+}
+
+// Swaps the music for the one of the state just entered
+fn play_state_music(
+    mut commands: Commands,
+    state: Res<State<GameState>>,
+    sounds: Res<Sounds>,
+    music: Query<Entity, With<Music>>,
+    volume: Res<Volume>,
+    music_volume: Res<MusicVolume>,
+) {
+    for entity in &music {
+        commands.entity(entity).despawn();
+    }
+    let track = match state.get() {
+        GameState::Menu => &sounds.menu_theme,
+        GameState::Space => &sounds.space_ambience,
+        GameState::Docked => &sounds.docked_music,
+        GameState::Splash | GameState::GameOver => return,
+    };
+    commands.spawn((
+        AudioPlayer(track.clone()),
+        PlaybackSettings::LOOP.with_volume(gain(*volume, music_volume.0)),
+        Music,
+    ));
+}
+
+// Applies a volume change to the music already playing
+fn update_music_volume(
+    mut sinks: Query<&mut AudioSink, With<Music>>,
+    volume: Res<Volume>,
+    music_volume: Res<MusicVolume>,
+) {
+    for mut sink in &mut sinks {
+        sink.set_volume(gain(*volume, music_volume.0));
+    }
+}
+
+// One-shot effects are short enough to keep the volume they started with, only the
+// looping engine sound follows the settings
+fn update_sfx_volume(
+    mut sinks: Query<&mut AudioSink, With<ThrustSound>>,
+    volume: Res<Volume>,
+    sfx_volume: Res<SfxVolume>,
+) {
+    for mut sink in &mut sinks {
+        sink.set_volume(gain(*volume, sfx_volume.0));
+    }
+}
+
+fn button_click_sound(
+    mut commands: Commands,
+    interactions: Query<&Interaction, (Changed<Interaction>, With<Button>)>,
+    sounds: Res<Sounds>,
+    asset_server: Res<AssetServer>,
+    volume: Res<Volume>,
+    sfx_volume: Res<SfxVolume>,
+) {
+    if interactions
+        .iter()
+        .any(|interaction| *interaction == Interaction::Pressed)
+    {
+        play_sfx(
+            &mut commands,
+            &asset_server,
+            &sounds.click,
+            *volume,
+            *sfx_volume,
+        );
+    }
+}
+
+// Keeps the engine sound looping while the player's ship burns fuel to accelerate
+fn thrust_sound(
+    mut commands: Commands,
+    ships: Query<(&ShipControlIntent, &Spaceship), With<PlayerControlled>>,
+    engine: Query<Entity, With<ThrustSound>>,
+    sounds: Res<Sounds>,
+    volume: Res<Volume>,
+    sfx_volume: Res<SfxVolume>,
+) {
+    let thrusting = ships
+        .iter()
+        .any(|(intent, ship)| intent.thrust > 0.0 && ship.fuel > 0.0);
+    match (thrusting, engine.single()) {
+        (true, Err(_)) => {
+            commands.spawn((
+                AudioPlayer(sounds.thrust.clone()),
+                PlaybackSettings::LOOP.with_volume(gain(*volume, sfx_volume.0)),
+                ThrustSound,
+                OnSpaceScreen,
+            ));
+        }
+        (false, Ok(entity)) => commands.entity(entity).despawn(),
+        _ => {}
+    }
+}
+
+// The engine goes quiet while the game is paused
+fn stop_thrust_sound(mut commands: Commands, engine: Query<Entity, With<ThrustSound>>) {
+    for entity in &engine {
+        commands.entity(entity).despawn();
+    }
+}
+
+// Sounds when the tank runs low, then again every few seconds until refuelled
+#[allow(clippy::too_many_arguments)]
+fn low_fuel_alarm(
+    mut commands: Commands,
+    ships: Query<&Spaceship, With<PlayerControlled>>,
+    sounds: Res<Sounds>,
+    asset_server: Res<AssetServer>,
+    volume: Res<Volume>,
+    sfx_volume: Res<SfxVolume>,
+    time: Res<Time>,
+    mut since_alarm: Local<Option<f32>>,
+) {
+    let Ok(ship) = ships.single() else {
+        return;
+    };
+    if ship.fuel >= MAX_FUEL * LOW_FUEL_FRACTION {
+        *since_alarm = None;
+        return;
+    }
+    let elapsed = since_alarm.map_or(f32::INFINITY, |since| since + time.delta_secs());
+    if elapsed >= LOW_FUEL_ALARM_INTERVAL {
+        play_sfx(
+            &mut commands,
+            &asset_server,
+            &sounds.low_fuel,
+            *volume,
+            *sfx_volume,
+        );
+        *since_alarm = Some(0.0);
+    } else {
+        *since_alarm = Some(elapsed);
+    }
+}
+
+// Sounds once each time the ship gets close enough to the Sun to burn
+fn sun_warning_sound(
+    mut commands: Commands,
+    warning: Res<SunDamageWarning>,
+    sounds: Res<Sounds>,
+    asset_server: Res<AssetServer>,
+    volume: Res<Volume>,
+    sfx_volume: Res<SfxVolume>,
+    mut was_warned: Local<bool>,
+) {
+    if warning.0 && !*was_warned {
+        play_sfx(
+            &mut commands,
+            &asset_server,
+            &sounds.sun_warning,
+            *volume,
+            *sfx_volume,
+        );
+    }
+    *was_warned = warning.0;
+}
@@ -0,0 +1,195 @@
+// Flies the player's ship to the waypoint and stops there. The autopilot is just another
+// pilot writing the ship's `ShipControlIntent`, steering like the computer pilots do, and
+// hands the controls back as soon as the player turns or throttles.
+use crate::GameState;
+use crate::ai::steering::{arrive, avoid, controls_towards};
+use crate::controls::{Action, ActionState};
+use crate::game_over::Wrecked;
+use crate::nav::Waypoint;
+use crate::pause::PauseState;
+use crate::physics::Velocity;
+use crate::physics::collision::{Collider, CollisionResponse};
+use crate::planets::sun::Sun;
+use crate::ship::control::{ControlSet, PlayerControlled, ShipControlIntent};
+use crate::ship::movement::FUEL_PER_THRUST;
+use crate::ship::spaceship::Spaceship;
+use crate::space::{OnSpaceScreen, SUN_DAMAGE_RADIUS};
+use bevy::prelude::*;
+
+const CRUISE_SPEED: f32 = 800.0;
+const SLOWING_RADIUS: f32 = 1500.0;
+// Fuel the autopilot never spends
+const FUEL_RESERVE: f32 = 5.0;
+// The ship stops this far from the surface of the body it flies to, close enough to dock
+const ARRIVAL_STANDOFF: f32 = 80.0;
+// The ship has arrived once it is this close to its stop and about as fast as it
+const ARRIVAL_DISTANCE: f32 = 40.0;
+const ARRIVAL_SPEED: f32 = 30.0;
+// Stops inside the Sun's heat are moved out to this far beyond it
+const SUN_CLEARANCE: f32 = 200.0;
+
+pub fn autopilot_plugin(app: &mut App) {
+    app.add_systems(OnEnter(GameState::Space), spawn_autopilot_label)
+        .add_systems(
+            Update,
+            (
+                toggle_autopilot.before(ControlSet::Intent),
+                fly_to_waypoint.in_set(ControlSet::Intent),
+                show_autopilot_label.after(fly_to_waypoint),
+            )
+                .run_if(in_state(PauseState::Running)),
+        );
+}
+
+// Tag component for the player's ship while the autopilot flies it
+#[derive(Component)]
+pub struct Autopilot;
+
+#[derive(Component)]
+struct AutopilotLabel;
+
+// The Autopilot action engages it when there is a waypoint, and disengages it. Turning
+// or throttling takes the controls back too.
+#[allow(clippy::type_complexity)]
+fn toggle_autopilot(
+    mut commands: Commands,
+    actions: Res<ActionState>,
+    ships: Query<(Entity, Has<Autopilot>), (With<PlayerControlled>, Without<Wrecked>)>,
+    waypoints: Query<(), With<Waypoint>>,
+) {
+    let Ok((ship, engaged)) = ships.single() else {
+        return;
+    };
+    let toggled = actions.just_pressed(Action::Autopilot);
+    let overridden = actions.turn() != 0.0 || actions.thrust() != 0.0;
+    if engaged && (toggled || overridden || waypoints.is_empty()) {
+        commands.entity(ship).remove::<Autopilot>();
+    } else if !engaged && toggled && !waypoints.is_empty() {
+        commands.entity(ship).insert(Autopilot);
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn fly_to_waypoint(
+    mut commands: Commands,
+    mut ships: Query<
+        (
+            Entity,
+            &Transform,
+            &Velocity,
+            &Spaceship,
+            &mut ShipControlIntent,
+        ),
+        (With<Autopilot>, Without<Wrecked>),
+    >,
+    waypoints: Query<(Entity, &Waypoint, &Transform)>,
+    bodies: Query<(Option<&Velocity>, Option<&Collider>)>,
+    suns: Query<(&Transform, &Collider), With<Sun>>,
+    obstacles: Query<
+        (Entity, &Transform, &Collider),
+        (With<CollisionResponse>, Without<Sun>, Without<Autopilot>),
+    >,
+) {
+    let Ok((ship, transform, velocity, spaceship, mut intent)) = ships.single_mut() else {
+        return;
+    };
+    let Ok((waypoint_entity, waypoint, waypoint_transform)) = waypoints.single() else {
+        return;
+    };
+    let position = transform.translation.truncate();
+    let forward = (transform.rotation * Vec3::Y).truncate();
+
+    // A body's waypoint moves with it, and the ship stops off its surface on the near side
+    let mut stop = waypoint_transform.translation.truncate();
+    let mut stop_velocity = Vec2::ZERO;
+    if let Some((body_velocity, collider)) = waypoint.follow.and_then(|body| bodies.get(body).ok())
+    {
+        stop_velocity = body_velocity.map(|v| v.0).unwrap_or_default();
+        let radius = collider.map(Collider::radius).unwrap_or_default();
+        let side = (position - stop).normalize_or(Vec2::NEG_Y);
+        stop += side * (radius + ARRIVAL_STANDOFF);
+    }
+    // The Sun is an obstacle as big as its body or the area where it burns, whichever
+    // reaches farther
+    let sun = suns.single().ok().map(|(sun, collider)| {
+        (
+            sun.translation.truncate(),
+            collider.radius().max(SUN_DAMAGE_RADIUS),
+        )
+    });
+    if let Some((sun_position, danger_radius)) = sun
+        && stop.distance(sun_position) < danger_radius + SUN_CLEARANCE
+    {
+        let away = (stop - sun_position).normalize_or(Vec2::NEG_Y);
+        stop = sun_position + away * (danger_radius + SUN_CLEARANCE);
+    }
+
+    if position.distance(stop) < ARRIVAL_DISTANCE
+        && velocity.0.distance(stop_velocity) < ARRIVAL_SPEED
+    {
+        commands.entity(ship).remove::<Autopilot>();
+        commands.entity(waypoint_entity).despawn();
+        *intent = ShipControlIntent::default();
+        return;
+    }
+
+    // Never faster than the fuel left, less the reserve, can speed up to and then stop from
+    let fuel = (spaceship.fuel - FUEL_RESERVE).max(0.0);
+    let cruise_speed = CRUISE_SPEED.min(fuel / (2.0 * FUEL_PER_THRUST));
+    let desired = stop_velocity
+        + arrive(position, stop, cruise_speed, SLOWING_RADIUS)
+        + avoid(
+            position,
+            velocity.0,
+            obstacles
+                .iter()
+                .filter(|(entity, _, _)| Some(*entity) != waypoint.follow)
+                .map(|(_, transform, collider)| {
+                    (transform.translation.truncate(), collider.radius())
+                })
+                .chain(sun),
+        );
+    let (turn, thrust) = controls_towards(forward, velocity.0, desired);
+    *intent = ShipControlIntent {
+        turn,
+        thrust,
+        fire: false,
+    };
+}
+
+fn spawn_autopilot_label(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.spawn((
+        Text::new("AUTOPILOT"),
+        TextFont {
+            font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+            font_size: 22.0,
+            ..default()
+        },
+        TextColor(Color::srgb(0.2, 0.8, 1.0)),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(24.0),
+            width: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        TextLayout::new_with_justify(JustifyText::Center),
+        Visibility::Hidden,
+        AutopilotLabel,
+        OnSpaceScreen,
+    ));
+}
+
+fn show_autopilot_label(
+    ships: Query<Has<Autopilot>, With<PlayerControlled>>,
+    mut labels: Query<&mut Visibility, With<AutopilotLabel>>,
+) {
+    let engaged = ships.iter().any(|engaged| engaged);
+    for mut visibility in &mut labels {
+        visibility.set_if_neq(if engaged {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+    }
+}
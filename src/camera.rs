@@ -0,0 +1,211 @@
+// The camera in space. It follows the player's ship a little ahead of where it is going and
+// zooms out as it speeds up, on top of the zoom the player picks with the mouse wheel. The
+// Camera Mode action switches to framing the ship with the selected navigation point, or to
+// a free look panned by dragging the mouse.
+use crate::GameState;
+use crate::controls::{Action, ActionState};
+use crate::nav::SelectedNavPoint;
+use crate::pause::PauseState;
+use crate::physics::{PhysicsSet, Velocity};
+use crate::ship::control::PlayerControlled;
+use bevy::input::mouse::{AccumulatedMouseMotion, AccumulatedMouseScroll, MouseScrollUnit};
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+
+const PIXELS_PER_LINE: f32 = 40.0;
+
+pub fn camera_plugin(app: &mut App) {
+    app.init_resource::<CameraSettings>()
+        .init_resource::<CameraController>()
+        .add_systems(OnEnter(GameState::Space), reset_camera)
+        .add_systems(
+            Update,
+            (
+                (cycle_camera_mode, wheel_zoom, free_look).before(move_camera),
+                move_camera.after(PhysicsSet::Collide),
+            )
+                .run_if(in_state(PauseState::Running)),
+        );
+}
+
+// Tuning of the camera. Zoom is the camera's scale, in world units per pixel.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct CameraSettings {
+    // How quickly the camera catches up with where it should be, per second
+    pub follow_rate: f32,
+    pub zoom_rate: f32,
+    // Zoom the player starts with, and the limits of both the player's and the camera's zoom
+    pub default_zoom: f32,
+    pub min_zoom: f32,
+    pub max_zoom: f32,
+    // Zoom factor for each line of the mouse wheel
+    pub wheel_step: f32,
+    // The camera leads the ship by this many seconds of flight, up to `max_look_ahead`
+    // world units
+    pub look_ahead: f32,
+    pub max_look_ahead: f32,
+    // Zoom added for each unit of speed, as a fraction of the player's zoom
+    pub speed_zoom: f32,
+    // Share of the screen around the ship and its target in target lock
+    pub lock_margin: f32,
+}
+
+impl Default for CameraSettings {
+    fn default() -> Self {
+        Self {
+            follow_rate: 6.0,
+            zoom_rate: 3.0,
+            default_zoom: 3.0,
+            min_zoom: 1.0,
+            max_zoom: 16.0,
+            wheel_step: 1.15,
+            look_ahead: 0.6,
+            max_look_ahead: 600.0,
+            speed_zoom: 1.0 / 1200.0,
+            lock_margin: 0.8,
+        }
+    }
+}
+
+// What the camera looks at
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CameraMode {
+    // The player's ship, with look-ahead
+    #[default]
+    Follow,
+    // The ship and the selected navigation point together, like Follow without a selection
+    TargetLock,
+    // Wherever the player drags it
+    FreeLook,
+}
+
+impl CameraMode {
+    fn next(self) -> Self {
+        match self {
+            CameraMode::Follow => CameraMode::TargetLock,
+            CameraMode::TargetLock => CameraMode::FreeLook,
+            CameraMode::FreeLook => CameraMode::Follow,
+        }
+    }
+}
+
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct CameraController {
+    pub mode: CameraMode,
+    // Zoom picked with the mouse wheel, before the speed adds to it
+    pub zoom: f32,
+    // Jump to the ship instead of gliding there, when entering space
+    snap: bool,
+}
+
+impl Default for CameraController {
+    fn default() -> Self {
+        Self {
+            mode: CameraMode::default(),
+            zoom: CameraSettings::default().default_zoom,
+            snap: true,
+        }
+    }
+}
+
+// The camera was left wherever the last visit to space ended
+fn reset_camera(mut controller: ResMut<CameraController>) {
+    controller.mode = CameraMode::Follow;
+    controller.snap = true;
+}
+
+fn cycle_camera_mode(actions: Res<ActionState>, mut controller: ResMut<CameraController>) {
+    if actions.just_pressed(Action::CameraMode) {
+        controller.mode = controller.mode.next();
+    }
+}
+
+fn wheel_zoom(
+    scroll: Res<AccumulatedMouseScroll>,
+    settings: Res<CameraSettings>,
+    mut controller: ResMut<CameraController>,
+) {
+    if scroll.delta.y == 0.0 {
+        return;
+    }
+    let lines = match scroll.unit {
+        MouseScrollUnit::Line => scroll.delta.y,
+        MouseScrollUnit::Pixel => scroll.delta.y / PIXELS_PER_LINE,
+    };
+    // Scrolling up zooms in
+    controller.zoom = (controller.zoom * settings.wheel_step.powf(-lines))
+        .clamp(settings.min_zoom, settings.max_zoom);
+}
+
+// Dragging with the left mouse button moves the camera in free look
+fn free_look(
+    controller: Res<CameraController>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    motion: Res<AccumulatedMouseMotion>,
+    mut cameras: Query<&mut Transform, With<Camera2d>>,
+) {
+    if controller.mode != CameraMode::FreeLook || !mouse.pressed(MouseButton::Left) {
+        return;
+    }
+    for mut transform in &mut cameras {
+        // Screen y grows downwards
+        let drag = motion.delta * transform.scale.x * Vec2::new(-1.0, 1.0);
+        transform.translation += drag.extend(0.0);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn move_camera(
+    time: Res<Time>,
+    settings: Res<CameraSettings>,
+    mut controller: ResMut<CameraController>,
+    selected: Res<SelectedNavPoint>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    ships: Query<(&Transform, &Velocity), With<PlayerControlled>>,
+    targets: Query<&Transform, (Without<PlayerControlled>, Without<Camera2d>)>,
+    mut cameras: Query<&mut Transform, (With<Camera2d>, Without<PlayerControlled>)>,
+) {
+    let Ok((ship, velocity)) = ships.single() else {
+        return;
+    };
+    let ship_position = ship.translation.truncate();
+    let speed = velocity.length();
+    let look_ahead = (velocity.0 * settings.look_ahead).clamp_length_max(settings.max_look_ahead);
+    let mut zoom = controller.zoom * (1.0 + speed * settings.speed_zoom);
+    let target = selected.0.and_then(|target| targets.get(target).ok());
+    let focus = match (controller.mode, target) {
+        (CameraMode::FreeLook, _) => None,
+        (CameraMode::TargetLock, Some(target)) => {
+            // Zoom out far enough to keep both on screen
+            let target_position = target.translation.truncate();
+            let half_span = (target_position - ship_position).abs() / 2.0;
+            if let Ok(window) = windows.single() {
+                let room = window.size() / 2.0 * settings.lock_margin;
+                zoom = zoom.max((half_span / room).max_element());
+            }
+            Some((ship_position + target_position) / 2.0)
+        }
+        (CameraMode::TargetLock | CameraMode::Follow, _) => Some(ship_position + look_ahead),
+    };
+    let zoom = zoom.clamp(settings.min_zoom, settings.max_zoom);
+
+    // Exponential smoothing, the same whatever the frame rate
+    let dt = time.delta_secs();
+    let (follow, zoom_blend) = if controller.snap {
+        controller.snap = false;
+        (1.0, 1.0)
+    } else {
+        (
+            1.0 - (-settings.follow_rate * dt).exp(),
+            1.0 - (-settings.zoom_rate * dt).exp(),
+        )
+    };
+    for mut transform in &mut cameras {
+        if let Some(focus) = focus {
+            let position = transform.translation.truncate().lerp(focus, follow);
+            transform.translation = position.extend(transform.translation.z);
+        }
+        let scale = transform.scale.x + (zoom - transform.scale.x) * zoom_blend;
+        transform.scale = Vec3::splat(scale);
+    }
+}
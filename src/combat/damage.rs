@@ -0,0 +1,220 @@
+use crate::combat::Health;
+use crate::ship::spaceship::Spaceship;
+use bevy::prelude::*;
+
+// What dealt the damage, which decides how shields and hull take it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DamageType {
+    // Heat from flying too close to the Sun
+    Solar,
+    // Impacts with bodies and debris
+    Kinetic,
+    // Shots from energy weapons
+    Energy,
+}
+
+// What kind of thing dealt the damage. The source entity may be gone by the time anyone
+// asks, raiders for one blow up as they hit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DamageSource {
+    // The Sun, planets and stations
+    Body,
+    // Shots from any ship's guns
+    Weapon,
+    // Z Defense raiders ramming their target
+    Raider,
+}
+
+// Weapons deal damage in points, like `Health`. A ship's hull and shields go from 0.0 to
+// 1.0, each worth this many points.
+pub const SHIP_HIT_POINTS: f32 = 20.0;
+
+// Every source of damage goes through this event, so that shields get their say
+// before the hull or health is touched. `amount` is in the target's own terms: a share of
+// the hull for ships, points for `Health`.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct DamageEvent {
+    pub target: Entity,
+    pub source: Option<Entity>,
+    pub source_kind: DamageSource,
+    pub amount: f32,
+    pub kind: DamageType,
+}
+
+// The last damage an entity took, to know who destroyed it and how
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct LastDamage {
+    pub source: Option<Entity>,
+    pub source_kind: DamageSource,
+    pub kind: DamageType,
+}
+
+// How shields interact with one type of damage
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShieldResponse {
+    // Fraction of the damage the shields try to absorb while they hold
+    pub absorb: f32,
+    // Shield energy drained for each point of damage absorbed
+    pub drain: f32,
+    // Multiplier on the damage that gets through to the hull
+    pub hull_multiplier: f32,
+}
+
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct ShieldSettings {
+    pub solar: ShieldResponse,
+    pub kinetic: ShieldResponse,
+    pub energy: ShieldResponse,
+    // Seconds without taking damage before the shields start recharging
+    pub regen_delay: f32,
+    // Shield energy recovered per second once recharging
+    pub regen_rate: f32,
+}
+
+impl Default for ShieldSettings {
+    fn default() -> Self {
+        Self {
+            // Shields are good at dispersing heat
+            solar: ShieldResponse {
+                absorb: 0.75,
+                drain: 1.0,
+                hull_multiplier: 1.0,
+            },
+            // Most of an impact's momentum goes straight through, and is costly to stop
+            kinetic: ShieldResponse {
+                absorb: 0.25,
+                drain: 1.5,
+                hull_multiplier: 1.0,
+            },
+            // Shields stop energy shots entirely, but they burn the bare hull
+            energy: ShieldResponse {
+                absorb: 1.0,
+                drain: 1.0,
+                hull_multiplier: 1.25,
+            },
+            regen_delay: 3.0,
+            regen_rate: 0.1,
+        }
+    }
+}
+
+impl ShieldSettings {
+    pub fn response(&self, kind: DamageType) -> ShieldResponse {
+        match kind {
+            DamageType::Solar => self.solar,
+            DamageType::Kinetic => self.kinetic,
+            DamageType::Energy => self.energy,
+        }
+    }
+}
+
+// Splits `amount` of damage between shields and hull. Returns the new shields and
+// hull levels.
+pub fn split_damage(shields: f32, hull: f32, amount: f32, response: ShieldResponse) -> (f32, f32) {
+    let absorbed = if response.drain > 0.0 {
+        (amount * response.absorb).min(shields / response.drain)
+    } else {
+        amount * response.absorb
+    };
+    let shields = (shields - absorbed * response.drain).max(0.0);
+    let hull = (hull - (amount - absorbed) * response.hull_multiplier).max(0.0);
+    (shields, hull)
+}
+
+// Delays shield recharge after the ship takes damage
+#[derive(Component, Debug, Clone)]
+pub struct ShieldRecharge {
+    pub delay: Timer,
+}
+
+impl ShieldRecharge {
+    pub fn new(delay: f32) -> Self {
+        Self {
+            delay: Timer::from_seconds(delay, TimerMode::Once),
+        }
+    }
+}
+
+pub fn apply_damage(
+    mut commands: Commands,
+    mut events: EventReader<DamageEvent>,
+    settings: Res<ShieldSettings>,
+    mut ships: Query<(&mut Spaceship, Option<&mut ShieldRecharge>)>,
+    mut targets: Query<&mut Health, Without<Spaceship>>,
+) {
+    for event in events.read() {
+        commands.entity(event.target).try_insert(LastDamage {
+            source: event.source,
+            source_kind: event.source_kind,
+            kind: event.kind,
+        });
+        if let Ok((mut ship, recharge)) = ships.get_mut(event.target) {
+            let (shields, hull) = split_damage(
+                ship.shields,
+                ship.hull,
+                event.amount,
+                settings.response(event.kind),
+            );
+            ship.shields = shields;
+            ship.hull = hull;
+            if let Some(mut recharge) = recharge {
+                recharge.delay.reset();
+            }
+        } else if let Ok(mut health) = targets.get_mut(event.target) {
+            health.current = (health.current - event.amount).max(0.0);
+        }
+    }
+}
+
+pub fn recharge_shields(
+    mut ships: Query<(&mut Spaceship, &mut ShieldRecharge)>,
+    settings: Res<ShieldSettings>,
+    time: Res<Time>,
+) {
+    for (mut ship, mut recharge) in &mut ships {
+        if recharge.delay.tick(time.delta()).finished() && ship.shields < 1.0 {
+            ship.shields = (ship.shields + settings.regen_rate * time.delta_secs()).min(1.0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Shots of `damage` points it takes to wreck a ship with full shields and hull
+    fn shots_to_kill(damage: f32, response: ShieldResponse) -> u32 {
+        let (mut shields, mut hull) = (1.0, 1.0);
+        let mut shots = 0;
+        while hull > 0.0 {
+            (shields, hull) = split_damage(shields, hull, damage / SHIP_HIT_POINTS, response);
+            shots += 1;
+        }
+        shots
+    }
+
+    #[test]
+    fn shields_absorb_until_drained() {
+        let response = ShieldSettings::default().energy;
+        let (shields, hull) = split_damage(1.0, 1.0, 0.25, response);
+        assert_eq!((shields, hull), (0.75, 1.0));
+        // What the shields cannot hold burns the hull, harder for energy shots
+        let (shields, hull) = split_damage(0.25, 1.0, 0.5, response);
+        assert_eq!(shields, 0.0);
+        assert!((hull - (1.0 - 0.25 * 1.25)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn partial_absorption_lets_the_rest_through() {
+        let response = ShieldSettings::default().kinetic;
+        let (shields, hull) = split_damage(1.0, 1.0, 0.4, response);
+        assert!((shields - (1.0 - 0.1 * 1.5)).abs() < 1e-6);
+        assert!((hull - 0.7).abs() < 1e-6);
+    }
+
+    #[test]
+    fn level_one_shots_take_a_fight_to_wreck_a_ship() {
+        // 20 shots drain the shields, 16 more burn through the hull
+        assert_eq!(shots_to_kill(1.0, ShieldSettings::default().energy), 36);
+    }
+}
@@ -0,0 +1,84 @@
+// Damage dealt by weapons and what happens to the things that take it
+use crate::combat::damage::LastDamage;
+use crate::economy::Wallet;
+use crate::pause::PauseState;
+use crate::physics::PhysicsSet;
+use crate::ship::control::PlayerControlled;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+pub mod damage;
+pub mod projectile;
+
+pub fn combat_plugin(app: &mut App) {
+    app.add_event::<damage::DamageEvent>()
+        .init_resource::<damage::ShieldSettings>()
+        .configure_sets(Update, CombatSet::ApplyDamage.after(PhysicsSet::Collide))
+        .add_systems(
+            Update,
+            (
+                projectile::expire_projectiles,
+                projectile::projectile_hits
+                    .after(PhysicsSet::Collide)
+                    .before(CombatSet::ApplyDamage),
+                (damage::apply_damage, despawn_destroyed)
+                    .chain()
+                    .in_set(CombatSet::ApplyDamage),
+                damage::recharge_shields.after(CombatSet::ApplyDamage),
+            )
+                .run_if(in_state(PauseState::Running)),
+        );
+}
+
+// Systems sending `DamageEvent`s run before the damage is applied
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum CombatSet {
+    ApplyDamage,
+}
+
+// Hit points of anything that can be shot at, other than ships which have a hull
+#[derive(Component, Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Health {
+    pub current: f32,
+    pub max: f32,
+}
+
+impl Health {
+    pub fn new(max: f32) -> Self {
+        Self { current: max, max }
+    }
+
+    pub fn is_destroyed(&self) -> bool {
+        self.current <= 0.0
+    }
+}
+
+// Credits paid to the pilot who destroys the entity
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bounty(pub u32);
+
+// Tag component for something the player has to protect. It is not despawned when its
+// health runs out, the game mode decides what losing it means.
+#[derive(Component)]
+pub struct Objective;
+
+#[allow(clippy::type_complexity)]
+fn despawn_destroyed(
+    mut commands: Commands,
+    query: Query<(Entity, &Health, Option<&Bounty>, Option<&LastDamage>), Without<Objective>>,
+    ships: Query<(), With<PlayerControlled>>,
+    mut wallet: ResMut<Wallet>,
+) {
+    for (entity, health, bounty, last_damage) in &query {
+        if health.is_destroyed() {
+            // Only kills made by the player's ship pay out
+            let killed_by_ship = last_damage
+                .and_then(|damage| damage.source)
+                .is_some_and(|source| ships.contains(source));
+            if let (Some(bounty), true) = (bounty, killed_by_ship) {
+                wallet.earn(bounty.0);
+            }
+            commands.entity(entity).despawn();
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use crate::combat::damage::{DamageEvent, DamageSource, DamageType, SHIP_HIT_POINTS};
+use crate::combat::{Health, Objective};
+use crate::physics::Velocity;
+use crate::physics::collision::{Collider, CollisionEvent};
+use crate::ship::spaceship::Spaceship;
+use crate::space::OnSpaceScreen;
+use bevy::platform::collections::HashSet;
+use bevy::prelude::*;
+
+const PROJECTILE_SIZE: Vec2 = Vec2::new(4.0, 16.0);
+const PROJECTILE_COLOR: Color = Color::srgb(1.0, 0.4, 0.2);
+
+#[derive(Component, Debug)]
+pub struct Projectile {
+    // Whoever fired it, never hit by its own shots
+    pub owner: Entity,
+    // In points, see `SHIP_HIT_POINTS`
+    pub damage: f32,
+    pub lifetime: Timer,
+}
+
+pub fn spawn_projectile(
+    commands: &mut Commands,
+    owner: Entity,
+    transform: Transform,
+    velocity: Vec2,
+    damage: f32,
+    lifetime: f32,
+) -> Entity {
+    commands
+        .spawn((
+            Sprite {
+                color: PROJECTILE_COLOR,
+                custom_size: Some(PROJECTILE_SIZE),
+                ..default()
+            },
+            transform,
+            Velocity(velocity),
+            Collider::aabb(1.0),
+            Projectile {
+                owner,
+                damage,
+                lifetime: Timer::from_seconds(lifetime, TimerMode::Once),
+            },
+            OnSpaceScreen,
+        ))
+        .id()
+}
+
+pub fn expire_projectiles(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Projectile)>,
+    time: Res<Time>,
+) {
+    for (entity, mut projectile) in &mut query {
+        if projectile.lifetime.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+// Projectiles damage what they hit if it has `Health` or is a ship, and are spent on
+// anything else solid except their owner and other projectiles. Objectives are spared
+// friendly fire.
+#[allow(clippy::type_complexity)]
+pub fn projectile_hits(
+    mut commands: Commands,
+    mut events: EventReader<CollisionEvent>,
+    projectiles: Query<&Projectile>,
+    targets: Query<(), (Or<(With<Health>, With<Spaceship>)>, Without<Objective>)>,
+    ships: Query<(), With<Spaceship>>,
+    mut damage_events: EventWriter<DamageEvent>,
+) {
+    let mut spent = HashSet::new();
+    for event in events.read() {
+        let Ok(projectile) = projectiles.get(event.entity) else {
+            continue;
+        };
+        if event.other == projectile.owner
+            || projectiles.contains(event.other)
+            || spent.contains(&event.entity)
+        {
+            continue;
+        }
+        if targets.contains(event.other) {
+            let amount = if ships.contains(event.other) {
+                projectile.damage / SHIP_HIT_POINTS
+            } else {
+                projectile.damage
+            };
+            damage_events.write(DamageEvent {
+                target: event.other,
+                source: Some(projectile.owner),
+                source_kind: DamageSource::Weapon,
+                amount,
+                kind: DamageType::Energy,
+            });
+        }
+        spent.insert(event.entity);
+        // It may also have expired this frame
+        commands.entity(event.entity).try_despawn();
+    }
+}
@@ -0,0 +1,73 @@
+// Files kept in the player's configuration directory, written as RON
+use bevy::prelude::*;
+use serde::{Serialize, de::DeserializeOwned};
+use std::path::PathBuf;
+
+const APP_DIR: &str = "z_defense";
+// Overrides the configuration directory, e.g. to keep tests away from the player's files
+const CONFIG_DIR_VAR: &str = "Z_DEFENSE_CONFIG_DIR";
+
+// The platform configuration directory for the game, `None` if it cannot be found
+pub fn config_dir() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os(CONFIG_DIR_VAR) {
+        return Some(PathBuf::from(dir));
+    }
+    let base = if cfg!(windows) {
+        std::env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        std::env::var_os("HOME").map(|home| {
+            PathBuf::from(home)
+                .join("Library")
+                .join("Application Support")
+        })
+    } else {
+        std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+    };
+    base.map(|base| base.join(APP_DIR))
+}
+
+// Reads a configuration file. Missing or unreadable files give `None`, so that the
+// caller falls back to its defaults.
+pub fn load<T: DeserializeOwned>(file_name: &str) -> Option<T> {
+    load_with(file_name, |contents| {
+        ron::from_str(contents).map_err(|error| error.to_string())
+    })
+}
+
+// Like `load`, for files that need more than deserializing, such as an upgrade from an
+// older format
+pub fn load_with<T>(file_name: &str, parse: impl FnOnce(&str) -> Result<T, String>) -> Option<T> {
+    let path = config_dir()?.join(file_name);
+    let contents = std::fs::read_to_string(&path).ok()?;
+    match parse(&contents) {
+        Ok(value) => Some(value),
+        Err(error) => {
+            warn!("Ignoring {}: {error}", path.display());
+            None
+        }
+    }
+}
+
+// Writes a configuration file, creating its directory if needed. `file_name` may be
+// in a subdirectory. Failures are logged and reported by returning `false`, the game
+// goes on with the values it has in memory.
+pub fn save<T: Serialize>(file_name: &str, value: &T) -> bool {
+    let Some(dir) = config_dir() else {
+        warn!("No configuration directory, {file_name} is not saved");
+        return false;
+    };
+    let path = dir.join(file_name);
+    let result = ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default())
+        .map_err(|error| error.to_string())
+        .and_then(|contents| {
+            std::fs::create_dir_all(path.parent().unwrap_or(&dir))
+                .and_then(|_| std::fs::write(&path, contents))
+                .map_err(|error| error.to_string())
+        });
+    if let Err(error) = &result {
+        warn!("Could not save {}: {error}", path.display());
+    }
+    result.is_ok()
+}
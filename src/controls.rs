@@ -0,0 +1,250 @@
+// Maps the keyboard and gamepads to the game's actions. Systems ask `ActionState`
+// whether an action is held instead of reading keys, so that the player can rebind them.
+use bevy::input::InputSystem;
+use bevy::input::gamepad::GamepadButton;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+
+// Stick deflection ignored around the center
+const STICK_DEAD_ZONE: f32 = 0.2;
+
+pub fn controls_plugin(app: &mut App) {
+    app.init_resource::<Bindings>()
+        .init_resource::<ActionState>()
+        .add_systems(PreUpdate, update_action_state.after(InputSystem));
+}
+
+// Everything the player can bind a key or a gamepad button to
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Action {
+    Thrust,
+    Brake,
+    RotateLeft,
+    RotateRight,
+    Fire,
+    Interact,
+    Pause,
+    Map,
+    RadarRange,
+    NextTarget,
+    SetWaypoint,
+    Autopilot,
+    CameraMode,
+}
+
+impl Action {
+    pub const ALL: [Action; 13] = [
+        Action::Thrust,
+        Action::Brake,
+        Action::RotateLeft,
+        Action::RotateRight,
+        Action::Fire,
+        Action::Interact,
+        Action::Pause,
+        Action::Map,
+        Action::RadarRange,
+        Action::NextTarget,
+        Action::SetWaypoint,
+        Action::Autopilot,
+        Action::CameraMode,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Action::Thrust => "Thrust",
+            Action::Brake => "Brake",
+            Action::RotateLeft => "Rotate left",
+            Action::RotateRight => "Rotate right",
+            Action::Fire => "Fire",
+            Action::Interact => "Interact",
+            Action::Pause => "Pause",
+            Action::Map => "Map",
+            Action::RadarRange => "Radar range",
+            Action::NextTarget => "Next target",
+            Action::SetWaypoint => "Set waypoint",
+            Action::Autopilot => "Autopilot",
+            Action::CameraMode => "Camera mode",
+        }
+    }
+}
+
+// The key and gamepad button bound to each action, saved with the other settings
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Bindings {
+    pub keyboard: BTreeMap<Action, KeyCode>,
+    pub gamepad: BTreeMap<Action, GamepadButton>,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Self {
+            keyboard: BTreeMap::from([
+                (Action::Thrust, KeyCode::ArrowUp),
+                (Action::Brake, KeyCode::ArrowDown),
+                (Action::RotateLeft, KeyCode::ArrowLeft),
+                (Action::RotateRight, KeyCode::ArrowRight),
+                (Action::Fire, KeyCode::Space),
+                (Action::Interact, KeyCode::KeyE),
+                (Action::Pause, KeyCode::Escape),
+                (Action::Map, KeyCode::KeyM),
+                (Action::RadarRange, KeyCode::KeyR),
+                (Action::NextTarget, KeyCode::KeyT),
+                (Action::SetWaypoint, KeyCode::KeyG),
+                (Action::Autopilot, KeyCode::KeyF),
+                (Action::CameraMode, KeyCode::KeyC),
+            ]),
+            gamepad: BTreeMap::from([
+                (Action::Thrust, GamepadButton::RightTrigger2),
+                (Action::Brake, GamepadButton::LeftTrigger2),
+                (Action::RotateLeft, GamepadButton::DPadLeft),
+                (Action::RotateRight, GamepadButton::DPadRight),
+                (Action::Fire, GamepadButton::South),
+                (Action::Interact, GamepadButton::West),
+                (Action::Pause, GamepadButton::Start),
+                (Action::Map, GamepadButton::Select),
+                (Action::RadarRange, GamepadButton::North),
+                (Action::NextTarget, GamepadButton::RightTrigger),
+                (Action::SetWaypoint, GamepadButton::LeftTrigger),
+                (Action::Autopilot, GamepadButton::East),
+                (Action::CameraMode, GamepadButton::RightThumb),
+            ]),
+        }
+    }
+}
+
+impl Bindings {
+    // Binds `key` to `action`. An action already using that key gets the action's
+    // previous key, so that no action is left unbound.
+    pub fn bind_key(&mut self, action: Action, key: KeyCode) {
+        rebind(&mut self.keyboard, action, key);
+    }
+
+    pub fn bind_button(&mut self, action: Action, button: GamepadButton) {
+        rebind(&mut self.gamepad, action, button);
+    }
+
+    // Gives actions added since the bindings were saved their default key and button,
+    // unless the player already uses them for something else
+    pub fn fill_missing(&mut self) {
+        let defaults = Bindings::default();
+        fill_missing(&mut self.keyboard, defaults.keyboard);
+        fill_missing(&mut self.gamepad, defaults.gamepad);
+    }
+}
+
+fn rebind<T: Copy + PartialEq>(bindings: &mut BTreeMap<Action, T>, action: Action, input: T) {
+    let previous = bindings.insert(action, input);
+    let taken_by = bindings
+        .iter()
+        .find(|(other, bound)| **other != action && **bound == input)
+        .map(|(other, _)| *other);
+    if let Some(other) = taken_by {
+        match previous {
+            Some(previous) => bindings.insert(other, previous),
+            None => bindings.remove(&other),
+        };
+    }
+}
+
+fn fill_missing<T: PartialEq>(bindings: &mut BTreeMap<Action, T>, defaults: BTreeMap<Action, T>) {
+    for (action, input) in defaults {
+        if !bindings.contains_key(&action) && !bindings.values().any(|bound| *bound == input) {
+            bindings.insert(action, input);
+        }
+    }
+}
+
+// Name of a key as shown to the player
+pub fn key_label(key: KeyCode) -> String {
+    let name = format!("{key:?}");
+    name.strip_prefix("Key")
+        .or_else(|| name.strip_prefix("Digit"))
+        .unwrap_or(&name)
+        .to_string()
+}
+
+// Name of a gamepad button as shown to the player
+pub fn button_label(button: GamepadButton) -> String {
+    format!("{button:?}")
+}
+
+// Which actions the player is asking for this frame, from every input device
+#[derive(Resource, Debug, Default)]
+pub struct ActionState {
+    pressed: HashSet<Action>,
+    just_pressed: HashSet<Action>,
+    // Left stick of the gamepads, summed
+    stick: Vec2,
+}
+
+impl ActionState {
+    pub fn pressed(&self, action: Action) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.just_pressed.contains(&action)
+    }
+
+    // From -1.0 (right) to 1.0 (left), the stick is used when no button is held
+    pub fn turn(&self) -> f32 {
+        let buttons = self.axis(Action::RotateLeft, Action::RotateRight);
+        if buttons != 0.0 {
+            buttons
+        } else {
+            -self.stick.x
+        }
+    }
+
+    // From -1.0 (brake) to 1.0 (full ahead), the stick is used when no button is held
+    pub fn thrust(&self) -> f32 {
+        let buttons = self.axis(Action::Thrust, Action::Brake);
+        if buttons != 0.0 {
+            buttons
+        } else {
+            self.stick.y
+        }
+    }
+
+    fn axis(&self, positive: Action, negative: Action) -> f32 {
+        let mut value = 0.0;
+        if self.pressed(positive) {
+            value += 1.0;
+        }
+        if self.pressed(negative) {
+            value -= 1.0;
+        }
+        value
+    }
+}
+
+fn update_action_state(
+    mut state: ResMut<ActionState>,
+    bindings: Res<Bindings>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+) {
+    state.pressed.clear();
+    state.just_pressed.clear();
+    for action in Action::ALL {
+        let key = bindings.keyboard.get(&action);
+        let button = bindings.gamepad.get(&action);
+        let pressed = key.is_some_and(|key| keyboard_input.pressed(*key))
+            || button.is_some_and(|button| gamepads.iter().any(|pad| pad.pressed(*button)));
+        let just_pressed = key.is_some_and(|key| keyboard_input.just_pressed(*key))
+            || button.is_some_and(|button| gamepads.iter().any(|pad| pad.just_pressed(*button)));
+        if pressed {
+            state.pressed.insert(action);
+        }
+        if just_pressed {
+            state.just_pressed.insert(action);
+        }
+    }
+    let stick: Vec2 = gamepads.iter().map(Gamepad::left_stick).sum();
+    state.stick = if stick.length() < STICK_DEAD_ZONE {
+        Vec2::ZERO
+    } else {
+        stick.clamp_length_max(1.0)
+    };
+}
@@ -0,0 +1,412 @@
+use bevy::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    DisplayQuality, GameState, TEXT_COLOR,
+    combat::{
+        Bounty, CombatSet, Health,
+        damage::{DamageEvent, DamageSource, DamageType},
+        projectile::Projectile,
+    },
+    economy::Wallet,
+    game_over::{RunStats, Wrecked, spawn_explosion},
+    pause::PauseState,
+    physics::{PhysicsSet, Velocity, collision::Collider, collision::CollisionEvent},
+    planets::{base::Base, sun::Sun},
+    quality::ui_refresh_due,
+    radar::RadarSignature,
+    ship::spaceship::Spaceship,
+    space::OnSpaceScreen,
+};
+
+// This plugin runs the Z Defense game mode:
+// - waves of raiders jump in at the edge of the system and fly at the Base
+// - raiders ram whatever they reach, the Base first of all
+// - between waves the Base opens for docking so the pilot can refit
+// - the run is lost when the Base's health runs out
+pub fn defense_plugin(app: &mut App) {
+    app.init_resource::<GameMode>()
+        .add_systems(
+            OnEnter(GameState::Space),
+            defense_setup.run_if(resource_equals(GameMode::ZDefense)),
+        )
+        .add_systems(
+            Update,
+            (
+                run_waves,
+                steer_raiders
+                    .after(PhysicsSet::Forces)
+                    .before(PhysicsSet::Integrate),
+                raider_impacts
+                    .after(PhysicsSet::Collide)
+                    .before(CombatSet::ApplyDamage),
+                base_defeat.after(CombatSet::ApplyDamage),
+                update_defense_hud.run_if(ui_refresh_due),
+            )
+                .run_if(in_state(PauseState::Running).and(resource_exists::<Waves>)),
+        );
+}
+
+// Health of the Base when a Z Defense game starts
+pub const BASE_HEALTH: f32 = 100.0;
+// Break before the first wave, and between the following ones
+const FIRST_BREAK: f32 = 20.0;
+const WAVE_BREAK: f32 = 30.0;
+// Time between two raiders of the same wave jumping in
+const SPAWN_INTERVAL: f32 = 1.5;
+// Raiders jump in this far from the Sun, on the side of the system facing the Base
+const SYSTEM_RADIUS: f32 = 16000.0;
+const ENTRY_ARC: f32 = std::f32::consts::FRAC_PI_4;
+// How quickly raiders turn their velocity towards the Base
+const RAIDER_AGILITY: f32 = 1.5;
+const RAIDER_MAX_SPEED: f32 = 700.0;
+const RAIDER_SIZE: f32 = 96.0;
+const RAIDER_SPRITE_WIDTH: f32 = 2048.0;
+// Hull lost by a ship a raider rams into
+const RAIDER_HULL_DAMAGE: f32 = 0.25;
+const RAIDER_BOUNTY: u32 = 60;
+// Credits paid per wave number for holding off a wave
+const WAVE_BONUS: u32 = 100;
+const BASE_EXPLOSION_SPEED: f32 = 900.0;
+
+// What the pilot plays, chosen when starting a new game
+#[derive(
+    Resource, Debug, Component, PartialEq, Eq, Clone, Copy, Default, Serialize, Deserialize,
+)]
+pub enum GameMode {
+    // Free flight around the system
+    #[default]
+    Exploration,
+    // Defend the Base against waves of raiders
+    ZDefense,
+}
+
+impl GameMode {
+    pub fn label(self) -> &'static str {
+        match self {
+            GameMode::Exploration => "Exploration",
+            GameMode::ZDefense => "Z Defense",
+        }
+    }
+}
+
+// Progress through the waves of a Z Defense game. It only exists in that mode, and
+// survives docking since only the space entities are despawned.
+#[derive(Resource, Debug, Clone, Serialize, Deserialize)]
+pub struct Waves {
+    // Current wave, or the last one held off during a break. 0 before the first wave.
+    pub wave: u32,
+    pub phase: WavePhase,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum WavePhase {
+    // Counting down to the next wave, stations accept ships in the meantime
+    Break(Timer),
+    // Raiders still to jump in, and the time until the next one does
+    Attack { to_spawn: u32, spawn_timer: Timer },
+}
+
+impl Default for Waves {
+    fn default() -> Self {
+        Self {
+            wave: 0,
+            phase: WavePhase::Break(Timer::from_seconds(FIRST_BREAK, TimerMode::Once)),
+        }
+    }
+}
+
+impl Waves {
+    pub fn docking_open(&self) -> bool {
+        matches!(self.phase, WavePhase::Break(_))
+    }
+}
+
+// How a wave is made up, each wave bigger and tougher than the last
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WaveStrength {
+    pub raiders: u32,
+    pub health: f32,
+    pub speed: f32,
+    // Health taken from the Base by each raider that reaches it
+    pub damage: f32,
+}
+
+impl WaveStrength {
+    pub fn for_wave(wave: u32) -> Self {
+        let level = wave.saturating_sub(1);
+        Self {
+            raiders: 3 + 2 * level,
+            health: 2.0 + 0.5 * level as f32,
+            speed: (300.0 + 20.0 * level as f32).min(RAIDER_MAX_SPEED),
+            damage: 4.0 + level as f32,
+        }
+    }
+}
+
+// Hostile ship flying at the Base, destroyed by whatever it rams into
+#[derive(Component, Debug)]
+pub struct Raider {
+    speed: f32,
+    damage: f32,
+}
+
+// Tag component for the wave status text
+#[derive(Component)]
+struct DefenseHud;
+
+fn defense_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    waves: Option<Res<Waves>>,
+    mut stats: ResMut<RunStats>,
+) {
+    // Coming back from a station keeps the waves where they were
+    if waves.is_none() {
+        commands.insert_resource(Waves::default());
+        stats.waves_survived = Some(0);
+    }
+    commands.spawn((
+        Text::default(),
+        TextFont {
+            font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+            font_size: 24.0,
+            ..default()
+        },
+        TextColor(TEXT_COLOR),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(12.0),
+            left: Val::Px(12.0),
+            ..default()
+        },
+        DefenseHud,
+        OnSpaceScreen,
+    ));
+}
+
+#[allow(clippy::too_many_arguments)]
+fn run_waves(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut waves: ResMut<Waves>,
+    raiders: Query<(), With<Raider>>,
+    sun_query: Query<&Transform, With<Sun>>,
+    base_query: Query<&Transform, With<Base>>,
+    mut wallet: ResMut<Wallet>,
+    mut stats: ResMut<RunStats>,
+    time: Res<Time>,
+) {
+    let wave = waves.wave;
+    let next_phase = match &mut waves.phase {
+        WavePhase::Break(timer) => {
+            if !timer.tick(time.delta()).finished() {
+                return;
+            }
+            let mut spawn_timer = Timer::from_seconds(SPAWN_INTERVAL, TimerMode::Repeating);
+            // The first raider jumps in as soon as the wave starts
+            spawn_timer.set_elapsed(spawn_timer.duration());
+            WavePhase::Attack {
+                to_spawn: WaveStrength::for_wave(wave + 1).raiders,
+                spawn_timer,
+            }
+        }
+        WavePhase::Attack {
+            to_spawn,
+            spawn_timer,
+        } => {
+            if *to_spawn > 0 {
+                spawn_timer.tick(time.delta());
+                let (Ok(sun), Ok(base)) = (sun_query.single(), base_query.single()) else {
+                    return;
+                };
+                if spawn_timer.finished() {
+                    let position =
+                        raider_entry_point(sun.translation.truncate(), base.translation.truncate());
+                    spawn_raider(
+                        &mut commands,
+                        &asset_server,
+                        position,
+                        WaveStrength::for_wave(wave),
+                    );
+                    *to_spawn -= 1;
+                }
+                return;
+            }
+            if !raiders.is_empty() {
+                return;
+            }
+            wallet.earn(WAVE_BONUS * wave);
+            stats.waves_survived = Some(wave);
+            WavePhase::Break(Timer::from_seconds(WAVE_BREAK, TimerMode::Once))
+        }
+    };
+    if matches!(next_phase, WavePhase::Attack { .. }) {
+        waves.wave += 1;
+    }
+    waves.phase = next_phase;
+}
+
+// Random point on the edge of the system, on the side the Base is on so that raiders
+// do not fly through the Sun on their way in
+fn raider_entry_point(sun: Vec2, base: Vec2) -> Vec2 {
+    let mut rng = rand::rng();
+    let side = (base - sun).try_normalize().unwrap_or(Vec2::X).to_angle();
+    let angle = side + rng.random_range(-ENTRY_ARC..ENTRY_ARC);
+    sun + Vec2::from_angle(angle) * SYSTEM_RADIUS
+}
+
+fn spawn_raider(
+    commands: &mut Commands,
+    asset_server: &Res<AssetServer>,
+    position: Vec2,
+    strength: WaveStrength,
+) {
+    commands.spawn((
+        Sprite {
+            image: asset_server.load("s1.png"),
+            ..default()
+        },
+        Transform::from_translation(position.extend(0.0))
+            .with_scale(Vec3::splat(RAIDER_SIZE / RAIDER_SPRITE_WIDTH)),
+        Raider {
+            speed: strength.speed,
+            damage: strength.damage,
+        },
+        Health::new(strength.health),
+        Bounty(RAIDER_BOUNTY),
+        Collider::aabb(0.7),
+        Velocity::default(),
+        RadarSignature::Hostile,
+        OnSpaceScreen,
+    ));
+}
+
+fn steer_raiders(
+    mut raiders: Query<(&Raider, &mut Transform, &mut Velocity)>,
+    base_query: Query<&Transform, (With<Base>, Without<Raider>)>,
+    time: Res<Time>,
+) {
+    let Ok(base) = base_query.single() else {
+        return;
+    };
+    let target = base.translation.truncate();
+    let grip = (RAIDER_AGILITY * time.delta_secs()).min(1.0);
+    for (raider, mut transform, mut velocity) in &mut raiders {
+        let direction = (target - transform.translation.truncate()).normalize_or_zero();
+        velocity.0 = velocity.lerp(direction * raider.speed, grip);
+        // The raider sprite points its nose down
+        if let Some(heading) = velocity.try_normalize() {
+            transform.rotation =
+                Quat::from_rotation_z(heading.to_angle() + std::f32::consts::FRAC_PI_2);
+        }
+    }
+}
+
+// Raiders blow up on the first solid thing they touch, damaging it if they can
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn raider_impacts(
+    mut commands: Commands,
+    mut collisions: EventReader<CollisionEvent>,
+    raiders: Query<(&Raider, &Transform)>,
+    bases: Query<(), (With<Base>, With<Health>)>,
+    ships: Query<(), With<Spaceship>>,
+    harmless: Query<(), Or<(With<Raider>, With<Projectile>)>>,
+    mut damage_events: EventWriter<DamageEvent>,
+    display_quality: Res<DisplayQuality>,
+) {
+    for event in collisions.read() {
+        let Ok((raider, transform)) = raiders.get(event.entity) else {
+            continue;
+        };
+        if harmless.contains(event.other) {
+            continue;
+        }
+        let amount = if bases.contains(event.other) {
+            Some(raider.damage)
+        } else if ships.contains(event.other) {
+            Some(RAIDER_HULL_DAMAGE)
+        } else {
+            None
+        };
+        if let Some(amount) = amount {
+            damage_events.write(DamageEvent {
+                target: event.other,
+                source: Some(event.entity),
+                source_kind: DamageSource::Raider,
+                amount,
+                kind: DamageType::Kinetic,
+            });
+        }
+        commands.entity(event.entity).try_despawn();
+        spawn_explosion(
+            &mut commands,
+            transform.translation,
+            raider.speed * 0.5,
+            *display_quality,
+        );
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn base_defeat(
+    mut commands: Commands,
+    base_query: Query<(Entity, &Health, &Transform), (With<Base>, Without<Wrecked>)>,
+    waves: Res<Waves>,
+    mut stats: ResMut<RunStats>,
+    display_quality: Res<DisplayQuality>,
+) {
+    let Ok((entity, health, transform)) = base_query.single() else {
+        return;
+    };
+    if !health.is_destroyed() {
+        return;
+    }
+    commands
+        .entity(entity)
+        .insert((Wrecked::new(), Visibility::Hidden));
+    spawn_explosion(
+        &mut commands,
+        transform.translation,
+        BASE_EXPLOSION_SPEED,
+        *display_quality,
+    );
+    stats.base_destroyed = true;
+    stats.cause_of_death = Some(format!("The Base fell to wave {}", waves.wave));
+}
+
+fn update_defense_hud(
+    waves: Res<Waves>,
+    raiders: Query<(), With<Raider>>,
+    base_query: Query<&Health, With<Base>>,
+    mut hud_query: Query<&mut Text, With<DefenseHud>>,
+) {
+    let Ok(mut text) = hud_query.single_mut() else {
+        return;
+    };
+    let status = match &waves.phase {
+        WavePhase::Break(timer) => {
+            let seconds = timer.remaining_secs().ceil();
+            if waves.wave == 0 {
+                format!("First wave in {seconds:.0}s")
+            } else {
+                format!("Wave {} held off, next wave in {seconds:.0}s", waves.wave)
+            }
+        }
+        WavePhase::Attack { to_spawn, .. } => format!(
+            "Wave {}: {} raiders left",
+            waves.wave,
+            *to_spawn as usize + raiders.iter().count()
+        ),
+    };
+    let integrity = base_query
+        .single()
+        .map(|health| health.current / health.max)
+        .unwrap_or_default();
+    let mut lines = vec![status, format!("Base integrity: {:.0}%", integrity * 100.0)];
+    if waves.docking_open() {
+        lines.push("The Base is open for docking".to_string());
+    }
+    text.0 = lines.join("\n");
+}
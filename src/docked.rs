@@ -0,0 +1,463 @@
+use bevy::{
+    color::palettes::css::{CRIMSON, DARK_SLATE_GRAY},
+    ecs::spawn::SpawnWith,
+    prelude::*,
+};
+
+use super::{
+    GameState, TEXT_COLOR, despawn_screen,
+    economy::{PriceTable, Service, Wallet},
+    save::{MANUAL_SLOTS, SaveRequest, SaveSlots, slot_label},
+    session::GameSession,
+    ship::spaceship::MAX_FUEL,
+};
+
+// This plugin manages the station screen shown while the ship is docked:
+// - a header with the name of the station
+// - a row of service tabs, each one displaying its own content panel, and a tab to
+//   save the game
+// - an "Undock" button that returns to space next to the station
+pub fn docked_plugin(app: &mut App) {
+    app
+        // Like the menu, the active service tab is an independent state that is only
+        // enabled while docked
+        .init_state::<DockedTab>()
+        .add_systems(OnEnter(GameState::Docked), docked_setup)
+        .add_systems(
+            Update,
+            (
+                docked_action,
+                button_system,
+                // Purchases change the wallet and saving the slots, so the tab is rebuilt
+                // to show new values
+                service_tab_setup.run_if(
+                    state_changed::<DockedTab>
+                        .or(resource_changed::<Wallet>)
+                        .or(resource_changed::<SaveSlots>),
+                ),
+            )
+                .run_if(in_state(GameState::Docked)),
+        )
+        .add_systems(
+            OnExit(GameState::Docked),
+            (despawn_screen::<OnDockedScreen>, docked_teardown),
+        );
+}
+
+// The station the ship is docked at. Inserted when pressing "Dock" and used to
+// place the ship back next to the station when undocking.
+#[derive(Resource, Debug, Clone)]
+pub struct DockedStation {
+    pub label: String,
+    pub position: Vec2,
+    // Distance from the station's center to its surface
+    pub radius: f32,
+    // Stations may be orbiting, the ship leaves with the same velocity
+    pub velocity: Vec2,
+    pub prices: Option<PriceTable>,
+}
+
+// State used for the currently displayed service tab
+#[derive(Clone, Copy, Default, Eq, PartialEq, Debug, Hash, States)]
+enum DockedTab {
+    Refuel,
+    Repair,
+    Armory,
+    Save,
+    #[default]
+    Disabled,
+}
+
+impl DockedTab {
+    const TABS: [DockedTab; 4] = [
+        DockedTab::Refuel,
+        DockedTab::Repair,
+        DockedTab::Armory,
+        DockedTab::Save,
+    ];
+
+    fn title(self) -> &'static str {
+        match self {
+            DockedTab::Refuel => "Refuel",
+            DockedTab::Repair => "Repair",
+            DockedTab::Armory => "Armory",
+            DockedTab::Save => "Save",
+            DockedTab::Disabled => "",
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            DockedTab::Refuel => "Top up the fuel tanks before heading back out.",
+            DockedTab::Repair => "Patch the hull and recharge the shield emitters.",
+            DockedTab::Armory => "Upgrade the ship's weapon systems.",
+            DockedTab::Save => "Log the voyage so far in one of the save slots.",
+            DockedTab::Disabled => "",
+        }
+    }
+
+    fn services(self) -> &'static [Service] {
+        match self {
+            DockedTab::Refuel => &[Service::Fuel],
+            DockedTab::Repair => &[Service::Hull, Service::Shields],
+            DockedTab::Armory => &[Service::Weapons],
+            DockedTab::Save | DockedTab::Disabled => &[],
+        }
+    }
+}
+
+// Tag component used to tag entities added on the docked screen
+#[derive(Component)]
+struct OnDockedScreen;
+
+// Tag component for the node holding the content of the active tab
+#[derive(Component)]
+struct ServiceTabContent;
+
+// Tag component used to mark the button of the active tab
+#[derive(Component)]
+struct SelectedTab;
+
+// All actions that can be triggered from a button click on the docked screen
+#[derive(Component, Clone, Copy)]
+enum DockedButtonAction {
+    Tab(DockedTab),
+    Buy(Service),
+    Save(usize),
+    Undock,
+}
+
+const NORMAL_BUTTON: Color = Color::srgb(0.15, 0.15, 0.15);
+const HOVERED_BUTTON: Color = Color::srgb(0.25, 0.25, 0.25);
+const HOVERED_PRESSED_BUTTON: Color = Color::srgb(0.25, 0.65, 0.25);
+const PRESSED_BUTTON: Color = Color::srgb(0.35, 0.75, 0.35);
+
+fn docked_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    station: Option<Res<DockedStation>>,
+    mut tab_state: ResMut<NextState<DockedTab>>,
+) {
+    let station_name = station
+        .map(|station| station.label.clone())
+        .unwrap_or_else(|| "Unknown Station".to_string());
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+
+    let button_node = Node {
+        width: Val::Px(160.0),
+        height: Val::Px(55.0),
+        margin: UiRect::all(Val::Px(10.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let button_text_style = (
+        TextFont {
+            font: font.clone(),
+            font_size: 28.0,
+            ..default()
+        },
+        TextColor(TEXT_COLOR),
+    );
+
+    let tab_button_node = button_node.clone();
+    let tab_text_style = button_text_style.clone();
+    commands.spawn((
+        Node {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        BackgroundColor(DARK_SLATE_GRAY.into()),
+        OnDockedScreen,
+        children![(
+            Node {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                padding: UiRect::all(Val::Px(20.0)),
+                ..default()
+            },
+            BackgroundColor(CRIMSON.into()),
+            children![
+                // Station name
+                (
+                    Text::new(format!("Docked at {station_name}")),
+                    TextFont {
+                        font: font.clone(),
+                        font_size: 48.0,
+                        ..default()
+                    },
+                    TextColor(TEXT_COLOR),
+                    Node {
+                        margin: UiRect::all(Val::Px(20.0)),
+                        ..default()
+                    },
+                ),
+                // Service tabs
+                (
+                    Node {
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    Children::spawn(SpawnWith(move |parent: &mut ChildSpawner| {
+                        for tab in DockedTab::TABS {
+                            parent.spawn((
+                                Button,
+                                tab_button_node.clone(),
+                                BackgroundColor(NORMAL_BUTTON),
+                                DockedButtonAction::Tab(tab),
+                                children![(Text::new(tab.title()), tab_text_style.clone())],
+                            ));
+                        }
+                    })),
+                ),
+                // Content of the active tab, filled by `service_tab_setup`
+                (
+                    Node {
+                        width: Val::Px(540.0),
+                        min_height: Val::Px(200.0),
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        justify_content: JustifyContent::Center,
+                        margin: UiRect::all(Val::Px(10.0)),
+                        padding: UiRect::all(Val::Px(16.0)),
+                        ..default()
+                    },
+                    BackgroundColor(Color::BLACK.with_alpha(0.3)),
+                    ServiceTabContent,
+                ),
+                (
+                    Button,
+                    button_node,
+                    BackgroundColor(NORMAL_BUTTON),
+                    DockedButtonAction::Undock,
+                    children![(Text::new("Undock"), button_text_style)],
+                ),
+            ]
+        )],
+    ));
+
+    tab_state.set(DockedTab::Refuel);
+}
+
+// Rebuilds the content panel and moves the tab highlight whenever the active tab changes
+#[allow(clippy::too_many_arguments)]
+fn service_tab_setup(
+    mut commands: Commands,
+    tab: Res<State<DockedTab>>,
+    asset_server: Res<AssetServer>,
+    station: Option<Res<DockedStation>>,
+    session: Res<GameSession>,
+    wallet: Res<Wallet>,
+    save_slots: Res<SaveSlots>,
+    content_query: Query<Entity, With<ServiceTabContent>>,
+    mut tab_buttons: Query<(Entity, &DockedButtonAction, &mut BackgroundColor)>,
+) {
+    let tab = *tab.get();
+    for (entity, action, mut background_color) in &mut tab_buttons {
+        if let DockedButtonAction::Tab(button_tab) = action {
+            if *button_tab == tab {
+                commands.entity(entity).insert(SelectedTab);
+                *background_color = PRESSED_BUTTON.into();
+            } else {
+                commands.entity(entity).remove::<SelectedTab>();
+                *background_color = NORMAL_BUTTON.into();
+            }
+        }
+    }
+
+    let Ok(content) = content_query.single() else {
+        return;
+    };
+    commands.entity(content).despawn_related::<Children>();
+    if tab == DockedTab::Disabled {
+        return;
+    }
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+    let text_font = TextFont {
+        font: font.clone(),
+        font_size: 22.0,
+        ..default()
+    };
+    let prices = station.and_then(|station| station.prices);
+    let ship = session.ship.as_ref().map(|snapshot| &snapshot.stats);
+    commands.entity(content).with_children(|parent| {
+        parent.spawn((
+            Text::new(tab.title()),
+            TextFont {
+                font,
+                font_size: 36.0,
+                ..default()
+            },
+            TextColor(TEXT_COLOR),
+        ));
+        parent.spawn((
+            Text::new(tab.description()),
+            text_font.clone(),
+            TextColor(TEXT_COLOR),
+            Node {
+                margin: UiRect::vertical(Val::Px(16.0)),
+                ..default()
+            },
+        ));
+        if tab == DockedTab::Save {
+            for slot in MANUAL_SLOTS {
+                let description = save_slots
+                    .get(slot)
+                    .map_or("Empty".to_string(), |summary| summary.describe());
+                parent.spawn((
+                    Node {
+                        width: Val::Percent(100.0),
+                        align_items: AlignItems::Center,
+                        justify_content: JustifyContent::SpaceBetween,
+                        margin: UiRect::vertical(Val::Px(4.0)),
+                        ..default()
+                    },
+                    children![
+                        (
+                            Text::new(format!("{}: {description}", slot_label(slot))),
+                            text_font.clone(),
+                            TextColor(TEXT_COLOR),
+                        ),
+                        (
+                            Button,
+                            Node {
+                                width: Val::Px(90.0),
+                                height: Val::Px(40.0),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            BackgroundColor(NORMAL_BUTTON),
+                            DockedButtonAction::Save(slot),
+                            children![(
+                                Text::new("Save"),
+                                text_font.clone(),
+                                TextColor(TEXT_COLOR)
+                            )],
+                        ),
+                    ],
+                ));
+            }
+            return;
+        }
+        let (Some(prices), Some(ship)) = (prices, ship) else {
+            parent.spawn((
+                Text::new("This station offers no services."),
+                text_font.clone(),
+                TextColor(TEXT_COLOR),
+            ));
+            return;
+        };
+        for &service in tab.services() {
+            let level = match service {
+                Service::Fuel => format!("{:.0}%", ship.fuel / MAX_FUEL * 100.0),
+                Service::Hull => format!("{:.0}%", ship.hull * 100.0),
+                Service::Shields => format!("{:.0}%", ship.shields * 100.0),
+                Service::Weapons => format!("level {}", ship.weapons),
+            };
+            let cost = prices.quote(service, ship);
+            let price = if cost == 0 {
+                "-".to_string()
+            } else {
+                format!("{cost} cr")
+            };
+            parent.spawn((
+                Node {
+                    width: Val::Percent(100.0),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::SpaceBetween,
+                    margin: UiRect::vertical(Val::Px(4.0)),
+                    ..default()
+                },
+                children![
+                    (
+                        Text::new(format!("{}: {level}", service.label())),
+                        text_font.clone(),
+                        TextColor(TEXT_COLOR),
+                    ),
+                    (Text::new(price), text_font.clone(), TextColor(TEXT_COLOR)),
+                    (
+                        Button,
+                        Node {
+                            width: Val::Px(90.0),
+                            height: Val::Px(40.0),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        BackgroundColor(NORMAL_BUTTON),
+                        DockedButtonAction::Buy(service),
+                        children![(Text::new("Buy"), text_font.clone(), TextColor(TEXT_COLOR))],
+                    ),
+                ],
+            ));
+        }
+        parent.spawn((
+            Text::new(format!("Credits: {}", wallet.credits)),
+            text_font,
+            TextColor(TEXT_COLOR),
+            Node {
+                margin: UiRect::top(Val::Px(16.0)),
+                ..default()
+            },
+        ));
+    });
+}
+
+// This system handles changing all buttons color based on mouse interaction
+#[allow(clippy::type_complexity)]
+fn button_system(
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor, Option<&SelectedTab>),
+        (Changed<Interaction>, With<Button>),
+    >,
+) {
+    for (interaction, mut background_color, selected) in &mut interaction_query {
+        *background_color = match (*interaction, selected) {
+            (Interaction::Pressed, _) | (Interaction::None, Some(_)) => PRESSED_BUTTON.into(),
+            (Interaction::Hovered, Some(_)) => HOVERED_PRESSED_BUTTON.into(),
+            (Interaction::Hovered, None) => HOVERED_BUTTON.into(),
+            (Interaction::None, None) => NORMAL_BUTTON.into(),
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn docked_action(
+    interaction_query: Query<
+        (&Interaction, &DockedButtonAction),
+        (Changed<Interaction>, With<Button>),
+    >,
+    mut tab_state: ResMut<NextState<DockedTab>>,
+    mut game_state: ResMut<NextState<GameState>>,
+    station: Option<Res<DockedStation>>,
+    mut session: ResMut<GameSession>,
+    mut wallet: ResMut<Wallet>,
+    mut save_requests: EventWriter<SaveRequest>,
+) {
+    for (interaction, action) in &interaction_query {
+        if *interaction == Interaction::Pressed {
+            match action {
+                DockedButtonAction::Tab(tab) => tab_state.set(*tab),
+                DockedButtonAction::Buy(service) => {
+                    // The ship is parked in the session while docked, so that is what we service
+                    let prices = station.as_ref().and_then(|station| station.prices);
+                    if let (Some(prices), Some(snapshot)) = (prices, session.ship.as_mut()) {
+                        prices.purchase(*service, &mut snapshot.stats, &mut wallet);
+                    }
+                }
+                DockedButtonAction::Save(slot) => {
+                    save_requests.write(SaveRequest(*slot));
+                }
+                DockedButtonAction::Undock => game_state.set(GameState::Space),
+            }
+        }
+    }
+}
+
+fn docked_teardown(mut tab_state: ResMut<NextState<DockedTab>>) {
+    tab_state.set(DockedTab::Disabled);
+}
@@ -0,0 +1,179 @@
+use crate::ship::spaceship::{MAX_FUEL, Spaceship};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+// Credits a new pilot starts with
+const STARTING_CREDITS: u32 = 500;
+// Weapons level cannot be upgraded past this point (the UI bar is full at 10)
+pub const MAX_WEAPONS_LEVEL: u32 = 10;
+
+pub fn economy_plugin(app: &mut App) {
+    app.init_resource::<Wallet>();
+}
+
+// The player's credits, spent on station services
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Wallet {
+    pub credits: u32,
+    // Everything earned since the start of the game, spending does not lower it
+    pub earned: u32,
+}
+
+impl Default for Wallet {
+    fn default() -> Self {
+        Self {
+            credits: STARTING_CREDITS,
+            earned: 0,
+        }
+    }
+}
+
+impl Wallet {
+    pub fn earn(&mut self, amount: u32) {
+        self.credits += amount;
+        self.earned += amount;
+    }
+}
+
+// Services a station can sell
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Service {
+    Fuel,
+    Hull,
+    Shields,
+    Weapons,
+}
+
+impl Service {
+    pub fn label(self) -> &'static str {
+        match self {
+            Service::Fuel => "Fuel",
+            Service::Hull => "Hull repair",
+            Service::Shields => "Shield recharge",
+            Service::Weapons => "Weapons upgrade",
+        }
+    }
+}
+
+// Prices charged by a station. Fuel, hull and shields are priced for a full refill
+// (from empty to full), weapons per level reached.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PriceTable {
+    pub fuel: u32,
+    pub hull: u32,
+    pub shields: u32,
+    pub weapons: u32,
+}
+
+impl PriceTable {
+    // Main base around Earth: cheap repairs and upgrades
+    pub const BASE: PriceTable = PriceTable {
+        fuel: 120,
+        hull: 200,
+        shields: 80,
+        weapons: 250,
+    };
+    // Mining outpost on the Moon: cheap fuel, everything else is shipped in
+    pub const MOON: PriceTable = PriceTable {
+        fuel: 60,
+        hull: 320,
+        shields: 140,
+        weapons: 400,
+    };
+
+    // Price of a full refill / the next upgrade, 0 when there is nothing to buy
+    pub fn quote(&self, service: Service, ship: &Spaceship) -> u32 {
+        match service {
+            Service::Fuel => refill_cost(self.fuel, ship.fuel / MAX_FUEL),
+            Service::Hull => refill_cost(self.hull, ship.hull),
+            Service::Shields => refill_cost(self.shields, ship.shields),
+            Service::Weapons if ship.weapons >= MAX_WEAPONS_LEVEL => 0,
+            Service::Weapons => self.weapons * (ship.weapons + 1),
+        }
+    }
+
+    // Buys as much of the service as the wallet allows. Refills can be partial,
+    // upgrades must be paid in full. Returns the credits spent.
+    pub fn purchase(&self, service: Service, ship: &mut Spaceship, wallet: &mut Wallet) -> u32 {
+        let cost = self.quote(service, ship);
+        // Fuel is counted in units of the tank, hull and shields from 0.0 to 1.0
+        let (full_price, gauge, capacity) = match service {
+            Service::Fuel => (self.fuel, &mut ship.fuel, MAX_FUEL),
+            Service::Hull => (self.hull, &mut ship.hull, 1.0),
+            Service::Shields => (self.shields, &mut ship.shields, 1.0),
+            Service::Weapons => {
+                if cost == 0 || wallet.credits < cost {
+                    return 0;
+                }
+                ship.weapons += 1;
+                wallet.credits -= cost;
+                return cost;
+            }
+        };
+        let spent = cost.min(wallet.credits);
+        if spent == 0 {
+            return 0;
+        }
+        *gauge = (*gauge + spent as f32 / full_price as f32 * capacity).min(capacity);
+        wallet.credits -= spent;
+        spent
+    }
+}
+
+// `level` is the fraction of the gauge already full
+fn refill_cost(full_price: u32, level: f32) -> u32 {
+    ((1.0 - level).max(0.0) * full_price as f32).ceil() as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ship_with_fuel(fuel: f32) -> Spaceship {
+        Spaceship {
+            throttle: 0.0,
+            fuel,
+            hull: 1.0,
+            shields: 1.0,
+            weapons: 1,
+        }
+    }
+
+    #[test]
+    fn fuel_is_quoted_for_what_the_tank_is_missing() {
+        let prices = PriceTable::BASE;
+        assert_eq!(prices.quote(Service::Fuel, &ship_with_fuel(MAX_FUEL)), 0);
+        assert_eq!(
+            prices.quote(Service::Fuel, &ship_with_fuel(MAX_FUEL / 2.0)),
+            60
+        );
+        assert_eq!(prices.quote(Service::Fuel, &ship_with_fuel(0.0)), 120);
+    }
+
+    #[test]
+    fn purchase_fills_a_part_full_tank() {
+        let prices = PriceTable::BASE;
+        let mut ship = ship_with_fuel(MAX_FUEL / 2.0);
+        let mut wallet = Wallet {
+            credits: 500,
+            earned: 0,
+        };
+        assert_eq!(prices.purchase(Service::Fuel, &mut ship, &mut wallet), 60);
+        assert_eq!(ship.fuel, MAX_FUEL);
+        assert_eq!(wallet.credits, 440);
+    }
+
+    #[test]
+    fn purchase_buys_what_the_wallet_allows() {
+        let prices = PriceTable::BASE;
+        let mut ship = ship_with_fuel(MAX_FUEL / 2.0);
+        let mut wallet = Wallet {
+            credits: 30,
+            earned: 0,
+        };
+        assert_eq!(prices.purchase(Service::Fuel, &mut ship, &mut wallet), 30);
+        // A quarter of the full price buys a quarter of the tank
+        assert!((ship.fuel - MAX_FUEL * 0.75).abs() < 1e-3);
+        assert_eq!(wallet.credits, 0);
+    }
+}
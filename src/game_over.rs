@@ -0,0 +1,374 @@
+use bevy::{color::palettes::css::CRIMSON, ecs::spawn::SpawnIter, prelude::*};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    DisplayQuality, GameState, TEXT_COLOR,
+    combat::{
+        CombatSet,
+        damage::{DamageSource, DamageType, LastDamage},
+    },
+    despawn_screen,
+    economy::Wallet,
+    pause::PauseState,
+    physics::Velocity,
+    planets::sun::Sun,
+    session::reset_game,
+    ship::{action_menu::ActionMenuTarget, control::PlayerControlled, spaceship::Spaceship},
+    space::OnSpaceScreen,
+};
+
+// This plugin handles the end of a run:
+// - the ship blowing up once its hull is gone, while space keeps going for a moment
+// - a summary screen with "Restart" and "Main Menu"
+pub fn game_over_plugin(app: &mut App) {
+    app.init_resource::<RunStats>()
+        .add_systems(
+            Update,
+            (
+                track_run_stats,
+                start_destruction.after(CombatSet::ApplyDamage),
+                destruction_sequence,
+                fade_explosion,
+            )
+                .run_if(in_state(PauseState::Running)),
+        )
+        .add_systems(OnEnter(GameState::GameOver), game_over_setup)
+        .add_systems(
+            Update,
+            (game_over_action, button_system).run_if(in_state(GameState::GameOver)),
+        )
+        .add_systems(
+            OnExit(GameState::GameOver),
+            despawn_screen::<OnGameOverScreen>,
+        );
+}
+
+// How long the wreck is shown before the summary screen
+const DESTRUCTION_TIME: f32 = 2.5;
+const EXPLOSION_PARTICLES: usize = 40;
+const EXPLOSION_SPEED: f32 = 250.0;
+
+// What happened during the current run, shown once it is over
+#[derive(Resource, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RunStats {
+    pub time_survived: f32,
+    pub distance_travelled: f32,
+    pub cause_of_death: Option<String>,
+    // The run ended because the Base fell rather than the ship
+    pub base_destroyed: bool,
+    // Waves fought off in Z Defense, `None` in other modes
+    pub waves_survived: Option<u32>,
+}
+
+// Tag component for a ship whose hull is gone, counting down to the summary screen.
+// Wrecked ships no longer answer the controls.
+#[derive(Component)]
+pub struct Wrecked(Timer);
+
+impl Wrecked {
+    pub fn new() -> Self {
+        Self(Timer::from_seconds(DESTRUCTION_TIME, TimerMode::Once))
+    }
+}
+
+#[derive(Component)]
+struct ExplosionParticle(Timer);
+
+// Tag component used to tag entities added on the game over screen
+#[derive(Component)]
+struct OnGameOverScreen;
+
+// All actions that can be triggered from a button click on the game over screen
+#[derive(Component)]
+enum GameOverButtonAction {
+    Restart,
+    BackToMainMenu,
+}
+
+const NORMAL_BUTTON: Color = Color::srgb(0.15, 0.15, 0.15);
+const HOVERED_BUTTON: Color = Color::srgb(0.25, 0.25, 0.25);
+const PRESSED_BUTTON: Color = Color::srgb(0.35, 0.75, 0.35);
+
+fn track_run_stats(
+    mut stats: ResMut<RunStats>,
+    ships: Query<&Velocity, (With<PlayerControlled>, Without<Wrecked>)>,
+    time: Res<Time>,
+) {
+    let Ok(velocity) = ships.single() else {
+        return;
+    };
+    stats.time_survived += time.delta_secs();
+    stats.distance_travelled += velocity.length() * time.delta_secs();
+}
+
+#[allow(clippy::type_complexity)]
+fn start_destruction(
+    mut commands: Commands,
+    ships: Query<
+        (Entity, &Spaceship, &Transform, Option<&LastDamage>),
+        (With<PlayerControlled>, Without<Wrecked>),
+    >,
+    sources: Query<(Option<&ActionMenuTarget>, Has<Sun>)>,
+    mut stats: ResMut<RunStats>,
+    display_quality: Res<DisplayQuality>,
+) {
+    for (entity, ship, transform, last_damage) in &ships {
+        if ship.hull > 0.0 {
+            continue;
+        }
+        commands
+            .entity(entity)
+            .insert((Wrecked::new(), Visibility::Hidden));
+        stats.cause_of_death = Some(describe_cause(last_damage, &sources));
+        spawn_explosion(
+            &mut commands,
+            transform.translation,
+            EXPLOSION_SPEED,
+            *display_quality,
+        );
+    }
+}
+
+// Debris flying out of `position`, faded out over the destruction sequence. There is
+// more or less of it depending on the display quality.
+pub fn spawn_explosion(
+    commands: &mut Commands,
+    position: Vec3,
+    speed: f32,
+    quality: DisplayQuality,
+) {
+    let particles = (EXPLOSION_PARTICLES as f32 * quality.profile().particle_scale) as usize;
+    let mut rng = rand::rng();
+    for _ in 0..particles {
+        let direction = Vec2::from_angle(rng.random_range(0.0..std::f32::consts::TAU));
+        let particle_speed = rng.random_range(0.2..1.0) * speed;
+        commands.spawn((
+            Sprite {
+                color: Color::hsl(rng.random_range(10.0..50.0), 1.0, 0.55),
+                custom_size: Some(Vec2::splat(rng.random_range(4.0..12.0))),
+                ..default()
+            },
+            Transform::from_translation(position),
+            Velocity(direction * particle_speed),
+            ExplosionParticle(Timer::from_seconds(
+                rng.random_range(0.6..DESTRUCTION_TIME),
+                TimerMode::Once,
+            )),
+            OnSpaceScreen,
+        ));
+    }
+}
+
+fn describe_cause(
+    last_damage: Option<&LastDamage>,
+    sources: &Query<(Option<&ActionMenuTarget>, Has<Sun>)>,
+) -> String {
+    let Some(last_damage) = last_damage else {
+        return "Unknown".to_string();
+    };
+    let source = match last_damage
+        .source
+        .and_then(|source| sources.get(source).ok())
+    {
+        Some((_, true)) => "the Sun".to_string(),
+        Some((Some(target), _)) => target.label.clone(),
+        _ => "something".to_string(),
+    };
+    match last_damage.kind {
+        DamageType::Solar => "Burned up by the Sun".to_string(),
+        DamageType::Kinetic if last_damage.source_kind == DamageSource::Raider => {
+            "Rammed by a raider".to_string()
+        }
+        DamageType::Kinetic => format!("Crashed into {source}"),
+        DamageType::Energy => "Shot down".to_string(),
+    }
+}
+
+fn destruction_sequence(
+    mut wrecks: Query<&mut Wrecked>,
+    time: Res<Time>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    for mut wreck in &mut wrecks {
+        if wreck.0.tick(time.delta()).just_finished() {
+            game_state.set(GameState::GameOver);
+        }
+    }
+}
+
+fn fade_explosion(
+    mut commands: Commands,
+    mut particles: Query<(Entity, &mut ExplosionParticle, &mut Sprite)>,
+    time: Res<Time>,
+) {
+    for (entity, mut particle, mut sprite) in &mut particles {
+        if particle.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+        } else {
+            let alpha = particle.0.fraction_remaining();
+            sprite.color.set_alpha(alpha);
+        }
+    }
+}
+
+fn game_over_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    stats: Res<RunStats>,
+    wallet: Res<Wallet>,
+) {
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+    let button_node = Node {
+        width: Val::Px(250.0),
+        height: Val::Px(65.0),
+        margin: UiRect::all(Val::Px(20.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let button_text_style = (
+        TextFont {
+            font: font.clone(),
+            font_size: 33.0,
+            ..default()
+        },
+        TextColor(TEXT_COLOR),
+    );
+    let stat_text_style = (
+        TextFont {
+            font: font.clone(),
+            font_size: 26.0,
+            ..default()
+        },
+        TextColor(TEXT_COLOR),
+    );
+
+    let seconds = stats.time_survived as u32;
+    let cause = stats
+        .cause_of_death
+        .clone()
+        .unwrap_or_else(|| "Unknown".to_string());
+    let title = if stats.base_destroyed {
+        "Base Destroyed"
+    } else {
+        "Ship Destroyed"
+    };
+    let mut summary = vec![
+        format!("Cause of death: {cause}"),
+        format!("Time survived: {}:{:02}", seconds / 60, seconds % 60),
+        format!("Distance travelled: {:.0}", stats.distance_travelled),
+        format!("Credits earned: {}", wallet.earned),
+    ];
+    if let Some(waves) = stats.waves_survived {
+        summary.push(format!("Waves survived: {waves}"));
+    }
+    commands.spawn((
+        Node {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        BackgroundColor(Color::BLACK),
+        OnGameOverScreen,
+        children![(
+            Node {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                padding: UiRect::all(Val::Px(20.0)),
+                ..default()
+            },
+            BackgroundColor(CRIMSON.into()),
+            children![
+                (
+                    Text::new(title),
+                    TextFont {
+                        font,
+                        font_size: 60.0,
+                        ..default()
+                    },
+                    TextColor(TEXT_COLOR),
+                    Node {
+                        margin: UiRect::all(Val::Px(20.0)),
+                        ..default()
+                    },
+                ),
+                (
+                    Node {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    Children::spawn(SpawnIter(
+                        summary
+                            .into_iter()
+                            .map(move |line| (Text::new(line), stat_text_style.clone()))
+                    )),
+                ),
+                (
+                    Node {
+                        margin: UiRect::top(Val::Px(20.0)),
+                        ..default()
+                    },
+                    children![
+                        (
+                            Button,
+                            button_node.clone(),
+                            BackgroundColor(NORMAL_BUTTON),
+                            GameOverButtonAction::Restart,
+                            children![(Text::new("Restart"), button_text_style.clone())],
+                        ),
+                        (
+                            Button,
+                            button_node,
+                            BackgroundColor(NORMAL_BUTTON),
+                            GameOverButtonAction::BackToMainMenu,
+                            children![(Text::new("Main Menu"), button_text_style)],
+                        ),
+                    ]
+                ),
+            ]
+        )],
+    ));
+}
+
+// This system handles changing all buttons color based on mouse interaction
+#[allow(clippy::type_complexity)]
+fn button_system(
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor),
+        (Changed<Interaction>, With<Button>),
+    >,
+) {
+    for (interaction, mut background_color) in &mut interaction_query {
+        *background_color = match *interaction {
+            Interaction::Pressed => PRESSED_BUTTON.into(),
+            Interaction::Hovered => HOVERED_BUTTON.into(),
+            Interaction::None => NORMAL_BUTTON.into(),
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn game_over_action(
+    interaction_query: Query<
+        (&Interaction, &GameOverButtonAction),
+        (Changed<Interaction>, With<Button>),
+    >,
+    mut commands: Commands,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    for (interaction, action) in &interaction_query {
+        if *interaction == Interaction::Pressed {
+            match action {
+                GameOverButtonAction::Restart => {
+                    reset_game(&mut commands);
+                    game_state.set(GameState::Space);
+                }
+                GameOverButtonAction::BackToMainMenu => game_state.set(GameState::Menu),
+            }
+        }
+    }
+}
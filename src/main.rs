@@ -1,8 +1,28 @@
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
+mod ai;
+mod audio;
+mod autopilot;
+mod camera;
+mod combat;
+mod config;
+mod controls;
+mod defense;
 mod docked;
+mod economy;
+mod game_over;
+mod map;
 mod menu;
+mod nav;
+mod pause;
+mod physics;
 mod planets;
+mod quality;
+mod radar;
+mod save;
+mod session;
+mod settings;
 mod ship;
 mod space;
 mod splash;
@@ -16,32 +36,62 @@ pub enum GameState {
     Menu,
     Space,
     Docked,
+    GameOver,
 }
 
-#[derive(Resource, Debug, Component, PartialEq, Eq, Clone, Copy)]
+#[derive(Resource, Debug, Component, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
 enum DisplayQuality {
     Low,
     Medium,
     High,
 }
 
-// One of the two settings that can be set through the menu. It will be a resource in the app
-#[derive(Resource, Debug, Component, PartialEq, Eq, Clone, Copy)]
+// Master volume, from 0 to 9. It will be a resource in the app
+#[derive(Resource, Debug, Component, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
 struct Volume(u32);
 
+// Volume of the music and of the sound effects, from 0 to 9, scaled by the master volume
+#[derive(Resource, Debug, Component, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+struct MusicVolume(u32);
+
+#[derive(Resource, Debug, Component, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+struct SfxVolume(u32);
+
 fn main() {
     App::new()
         .add_plugins(DefaultPlugins)
         .init_state::<GameState>()
-        .insert_resource(DisplayQuality::Medium)
-        .insert_resource(Volume(7))
-        .insert_resource(space::SunDamageWarning::default()) // <-- Add this line
+        .insert_resource(space::SunDamageWarning::default())
         .add_systems(Startup, setup_camera)
         .add_plugins((
             splash::splash_plugin,
             menu::menu_plugin,
             space::space_plugin,
-            // docked::docked_plugin,
+            docked::docked_plugin,
+            session::session_plugin,
+            economy::economy_plugin,
+            physics::physics_plugin,
+            combat::combat_plugin,
+            game_over::game_over_plugin,
+            defense::defense_plugin,
+            ai::ai_plugin,
+            save::save_plugin,
+            pause::pause_plugin,
+        ))
+        // Navigation aids
+        .add_plugins((
+            radar::radar_plugin,
+            nav::nav_plugin,
+            autopilot::autopilot_plugin,
+            map::map_plugin,
+            camera::camera_plugin,
+        ))
+        // Player settings and what they drive
+        .add_plugins((
+            controls::controls_plugin,
+            settings::settings_plugin,
+            quality::quality_plugin,
+            audio::audio_plugin,
         ))
         .run();
 }
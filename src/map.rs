@@ -0,0 +1,502 @@
+// Full-screen map of the star system, opened with the Map action while in space. The game
+// is frozen while it is shown. It draws the Sun and its heat, the bodies with their orbits,
+// the player's ship and the waypoint at schematic scale. Dragging or the flight controls
+// pan it, the mouse wheel zooms, and a click sets the waypoint.
+use crate::GameState;
+use crate::controls::{Action, ActionState, Bindings, key_label};
+use crate::menu::MenuState;
+use crate::nav::{NavPoint, Waypoint, WaypointRequest};
+use crate::pause::PauseState;
+use crate::physics::collision::Collider;
+use crate::planets::orbit::Orbit;
+use crate::planets::sun::Sun;
+use crate::radar::RadarSignature;
+use crate::ship::control::PlayerControlled;
+use crate::space::SUN_DAMAGE_RADIUS;
+use crate::{TEXT_COLOR, despawn_screen};
+use bevy::input::mouse::{AccumulatedMouseScroll, MouseScrollUnit};
+use bevy::prelude::*;
+use bevy::window::{PrimaryWindow, WindowResized};
+
+const MAP_BACKGROUND: Color = Color::srgb(0.02, 0.02, 0.06);
+const ORBIT_COLOR: Color = Color::srgba(0.5, 0.6, 0.8, 0.4);
+const HAZARD_COLOR: Color = Color::srgba(1.0, 0.3, 0.1, 0.2);
+const WAYPOINT_COLOR: Color = Color::srgb(1.0, 0.85, 0.2);
+// Share of the screen taken by the whole system when the map opens
+const FIT_MARGIN: f32 = 0.9;
+// Zoom limits relative to the opening zoom
+const MIN_ZOOM: f32 = 0.5;
+const MAX_ZOOM: f32 = 40.0;
+// Zoom factor per line of the mouse wheel
+const WHEEL_ZOOM: f32 = 1.15;
+const PIXELS_PER_LINE: f32 = 40.0;
+// Panning with the flight controls, in pixels per second
+const PAN_SPEED: f32 = 600.0;
+// A press moving the cursor less than this many pixels is a click rather than a drag
+const DRAG_THRESHOLD: f32 = 4.0;
+// Bodies are drawn at least this many pixels across, and clicks this close pick them
+const MIN_BODY_SIZE: f32 = 10.0;
+const PICK_DISTANCE: f32 = 12.0;
+const SHIP_MARKER_SIZE: f32 = 24.0;
+const WAYPOINT_MARKER_SIZE: f32 = 12.0;
+const LABEL_WIDTH: f32 = 120.0;
+
+pub fn map_plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        toggle_map.run_if(in_state(GameState::Space).and(in_state(MenuState::Disabled))),
+    )
+    .add_systems(OnEnter(PauseState::Map), (freeze_time, map_setup))
+    .add_systems(
+        Update,
+        (
+            (zoom_map, pan_map, click_map).before(place_map_items),
+            update_waypoint_marker.before(place_map_items),
+            place_map_items.run_if(
+                resource_changed::<MapView>
+                    .or(any_match_filter::<Changed<MapItem>>)
+                    .or(on_event::<WindowResized>),
+            ),
+        )
+            .run_if(in_state(PauseState::Map).and(in_state(MenuState::Disabled))),
+    )
+    .add_systems(
+        OnExit(PauseState::Map),
+        (despawn_screen::<OnMapScreen>, resume_time),
+    );
+}
+
+// Tag component used to tag entities added on the map screen
+#[derive(Component)]
+struct OnMapScreen;
+
+// What part of the system the map shows. `scale` is in pixels per world unit, `fit` is the
+// scale the map opened at.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+struct MapView {
+    center: Vec2,
+    scale: f32,
+    fit: f32,
+}
+
+impl MapView {
+    // Position on the screen of a point in the world, y grows downwards on the screen
+    fn to_screen(self, screen_center: Vec2, world: Vec2) -> Vec2 {
+        screen_center + (world - self.center) * self.scale * Vec2::new(1.0, -1.0)
+    }
+
+    fn to_world(self, screen_center: Vec2, screen: Vec2) -> Vec2 {
+        self.center + (screen - screen_center) / self.scale * Vec2::new(1.0, -1.0)
+    }
+}
+
+// Something drawn on the map, centered on `position`. It is `radius` world units in size,
+// but never smaller than `min_size` pixels across.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+struct MapItem {
+    position: Vec2,
+    radius: f32,
+    min_size: f32,
+}
+
+// A body that can be clicked to put the waypoint on it
+#[derive(Component)]
+struct MapBody(Entity);
+
+#[derive(Component)]
+struct WaypointMarker;
+
+fn toggle_map(
+    actions: Res<ActionState>,
+    pause_state: Res<State<PauseState>>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+) {
+    if actions.just_pressed(Action::Map) {
+        match pause_state.get() {
+            PauseState::Running => next_pause_state.set(PauseState::Map),
+            PauseState::Map => next_pause_state.set(PauseState::Running),
+            PauseState::Paused => {}
+        }
+    }
+}
+
+fn freeze_time(mut time: ResMut<Time<Virtual>>) {
+    time.pause();
+}
+
+fn resume_time(mut time: ResMut<Time<Virtual>>) {
+    time.unpause();
+}
+
+// The world stands still while the map is open, so everything but the waypoint is drawn
+// once from where it is now
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn map_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    bindings: Res<Bindings>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    suns: Query<(&Transform, &Collider), With<Sun>>,
+    orbits: Query<&Orbit>,
+    // A location waypoint is a nav point too, but it is drawn as the waypoint marker
+    bodies: Query<
+        (
+            Entity,
+            &Transform,
+            &NavPoint,
+            Option<&Collider>,
+            Option<&RadarSignature>,
+        ),
+        Without<Waypoint>,
+    >,
+    ships: Query<&Transform, With<PlayerControlled>>,
+    positions: Query<&Transform>,
+) {
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+    let label_font = TextFont {
+        font: font.clone(),
+        font_size: 14.0,
+        ..default()
+    };
+    let map_key = bindings
+        .keyboard
+        .get(&Action::Map)
+        .map(|key| key_label(*key))
+        .unwrap_or_default();
+    let root = commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                overflow: Overflow::clip(),
+                ..default()
+            },
+            BackgroundColor(MAP_BACKGROUND),
+            // Above the HUD and the action menu, below the pause overlay
+            GlobalZIndex(5),
+            OnMapScreen,
+            children![(
+                Text::new(format!(
+                    "Click to set the waypoint - Drag to pan - Wheel to zoom - {map_key} to close"
+                )),
+                TextFont {
+                    font: font.clone(),
+                    font_size: 18.0,
+                    ..default()
+                },
+                TextColor(TEXT_COLOR),
+                Node {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(16.0),
+                    width: Val::Percent(100.0),
+                    ..default()
+                },
+                TextLayout::new_with_justify(JustifyText::Center),
+            )],
+        ))
+        .id();
+    // Everything drawn, to open the map on the whole system
+    let mut bounds: Option<Rect> = None;
+    let mut include = |item: MapItem| {
+        let rect = Rect::from_center_half_size(item.position, Vec2::splat(item.radius));
+        bounds = Some(bounds.map_or(rect, |bounds| bounds.union(rect)));
+        item
+    };
+
+    // Orbits are drawn as circles around their center, they are all close to round
+    for orbit in &orbits {
+        let Ok(parent) = positions.get(orbit.parent) else {
+            continue;
+        };
+        let periapsis = Vec2::from_angle(orbit.periapsis_angle);
+        let center =
+            parent.translation.truncate() - periapsis * orbit.semi_major_axis * orbit.eccentricity;
+        commands.spawn((
+            include(MapItem {
+                position: center,
+                radius: orbit.semi_major_axis,
+                min_size: 0.0,
+            }),
+            Node {
+                border: UiRect::all(Val::Px(1.0)),
+                ..item_node()
+            },
+            BorderColor(ORBIT_COLOR),
+            BorderRadius::MAX,
+            ChildOf(root),
+        ));
+    }
+    if let Ok((sun, collider)) = suns.single() {
+        let position = sun.translation.truncate();
+        let (_, color, _) = RadarSignature::Sun.icon();
+        commands.spawn((
+            include(MapItem {
+                position,
+                radius: collider.radius(),
+                min_size: MIN_BODY_SIZE,
+            }),
+            item_node(),
+            BackgroundColor(color),
+            BorderRadius::MAX,
+            ChildOf(root),
+            children![body_label("Sun", label_font.clone())],
+        ));
+        // Where the Sun's heat damages the hull, drawn over its body since it lies within
+        commands.spawn((
+            MapItem {
+                position,
+                radius: SUN_DAMAGE_RADIUS,
+                min_size: 0.0,
+            },
+            Node {
+                border: UiRect::all(Val::Px(1.0)),
+                ..item_node()
+            },
+            BackgroundColor(HAZARD_COLOR),
+            BorderColor(HAZARD_COLOR.with_alpha(0.6)),
+            BorderRadius::MAX,
+            ChildOf(root),
+        ));
+    }
+    for (entity, transform, point, collider, signature) in &bodies {
+        let (_, color, _) = signature.copied().unwrap_or(RadarSignature::Station).icon();
+        commands.spawn((
+            include(MapItem {
+                position: transform.translation.truncate(),
+                radius: collider.map(Collider::radius).unwrap_or_default(),
+                min_size: MIN_BODY_SIZE,
+            }),
+            item_node(),
+            BackgroundColor(color),
+            BorderRadius::MAX,
+            MapBody(entity),
+            ChildOf(root),
+            children![body_label(&point.label, label_font.clone())],
+        ));
+    }
+    // The ship is an arrow turned to its heading
+    if let Ok(ship) = ships.single() {
+        let forward = (ship.rotation * Vec3::Y).truncate();
+        commands.spawn((
+            include(MapItem {
+                position: ship.translation.truncate(),
+                radius: 0.0,
+                min_size: SHIP_MARKER_SIZE,
+            }),
+            Node {
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..item_node()
+            },
+            ChildOf(root),
+            children![(
+                Text::new("\u{2192}"),
+                TextFont {
+                    font,
+                    font_size: SHIP_MARKER_SIZE,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                // Screen y grows downwards
+                Transform::from_rotation(Quat::from_rotation_z((-forward.y).atan2(forward.x))),
+            )],
+        ));
+    }
+    // Placed by `update_waypoint_marker`
+    commands.spawn((
+        MapItem {
+            position: Vec2::ZERO,
+            radius: 0.0,
+            min_size: WAYPOINT_MARKER_SIZE,
+        },
+        item_node(),
+        BackgroundColor(WAYPOINT_COLOR),
+        Transform::from_rotation(Quat::from_rotation_z(std::f32::consts::FRAC_PI_4)),
+        Visibility::Hidden,
+        WaypointMarker,
+        ChildOf(root),
+    ));
+
+    let bounds = bounds.unwrap_or(Rect::from_center_half_size(Vec2::ZERO, Vec2::ONE));
+    let screen = windows
+        .single()
+        .map(|window| window.size())
+        .unwrap_or(Vec2::new(1280.0, 720.0));
+    let fit = (screen * FIT_MARGIN / bounds.size().max(Vec2::ONE)).min_element();
+    commands.insert_resource(MapView {
+        center: bounds.center(),
+        scale: fit,
+        fit,
+    });
+}
+
+fn item_node() -> Node {
+    Node {
+        position_type: PositionType::Absolute,
+        ..default()
+    }
+}
+
+fn body_label(label: &str, text_font: TextFont) -> impl Bundle {
+    (
+        Text::new(label),
+        text_font,
+        TextColor(TEXT_COLOR),
+        TextLayout::new_with_justify(JustifyText::Center),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Percent(100.0),
+            left: Val::Percent(50.0),
+            width: Val::Px(LABEL_WIDTH),
+            margin: UiRect::new(
+                Val::Px(-LABEL_WIDTH / 2.0),
+                Val::ZERO,
+                Val::Px(4.0),
+                Val::ZERO,
+            ),
+            ..default()
+        },
+    )
+}
+
+// Zooms around the point under the cursor, or the middle of the screen
+fn zoom_map(
+    scroll: Res<AccumulatedMouseScroll>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut view: ResMut<MapView>,
+) {
+    if scroll.delta.y == 0.0 {
+        return;
+    }
+    let Ok(window) = windows.single() else {
+        return;
+    };
+    let lines = match scroll.unit {
+        MouseScrollUnit::Line => scroll.delta.y,
+        MouseScrollUnit::Pixel => scroll.delta.y / PIXELS_PER_LINE,
+    };
+    let screen_center = window.size() / 2.0;
+    let cursor = window.cursor_position().unwrap_or(screen_center);
+    let anchor = view.to_world(screen_center, cursor);
+    let scale =
+        (view.scale * WHEEL_ZOOM.powf(lines)).clamp(view.fit * MIN_ZOOM, view.fit * MAX_ZOOM);
+    view.scale = scale;
+    // Keep the anchor under the cursor
+    view.center = anchor - (cursor - screen_center) / scale * Vec2::new(1.0, -1.0);
+}
+
+// Pans with the flight controls, and by dragging with the mouse. The game's clock is
+// frozen, the real one keeps the speed steady.
+fn pan_map(
+    actions: Res<ActionState>,
+    time: Res<Time<Real>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut view: ResMut<MapView>,
+    mut drag: Local<Option<(Vec2, Vec2)>>,
+) {
+    let pan = Vec2::new(-actions.turn(), actions.thrust());
+    if pan != Vec2::ZERO {
+        let step = pan * PAN_SPEED * time.delta_secs() / view.scale;
+        view.center += step;
+    }
+    let cursor = windows
+        .single()
+        .ok()
+        .and_then(|window| window.cursor_position());
+    if mouse.just_pressed(MouseButton::Left) {
+        *drag = cursor.map(|cursor| (cursor, view.center));
+    }
+    if !mouse.pressed(MouseButton::Left) {
+        *drag = None;
+    }
+    if let (Some((start, start_center)), Some(cursor)) = (*drag, cursor)
+        && cursor.distance(start) >= DRAG_THRESHOLD
+    {
+        let center = start_center - (cursor - start) / view.scale * Vec2::new(1.0, -1.0);
+        view.set_if_neq(MapView { center, ..*view });
+    }
+}
+
+// A click on a body puts the waypoint on it, anywhere else sets it there
+fn click_map(
+    mouse: Res<ButtonInput<MouseButton>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    view: Res<MapView>,
+    bodies: Query<(&MapBody, &MapItem)>,
+    mut press: Local<Option<Vec2>>,
+    mut requests: EventWriter<WaypointRequest>,
+) {
+    let Ok(window) = windows.single() else {
+        return;
+    };
+    let cursor = window.cursor_position();
+    if mouse.just_pressed(MouseButton::Left) {
+        *press = cursor;
+    }
+    if !mouse.just_released(MouseButton::Left) {
+        return;
+    }
+    let (Some(start), Some(cursor)) = (press.take(), cursor) else {
+        return;
+    };
+    if cursor.distance(start) >= DRAG_THRESHOLD {
+        return;
+    }
+    let screen_center = window.size() / 2.0;
+    let picked = bodies
+        .iter()
+        .map(|(MapBody(entity), item)| {
+            let size = (item.radius * view.scale).max(item.min_size / 2.0);
+            let distance = view
+                .to_screen(screen_center, item.position)
+                .distance(cursor);
+            (*entity, distance - size)
+        })
+        .filter(|(_, distance)| *distance < PICK_DISTANCE)
+        .min_by(|a, b| a.1.total_cmp(&b.1));
+    requests.write(match picked {
+        Some((body, _)) => WaypointRequest::Follow(body),
+        None => WaypointRequest::At(view.to_world(screen_center, cursor)),
+    });
+}
+
+fn update_waypoint_marker(
+    waypoints: Query<&Transform, With<Waypoint>>,
+    mut markers: Query<(&mut MapItem, &mut Visibility), With<WaypointMarker>>,
+) {
+    let waypoint = waypoints.iter().next();
+    for (mut item, mut visibility) in &mut markers {
+        match waypoint {
+            Some(transform) => {
+                let position = transform.translation.truncate();
+                if item.position != position {
+                    item.position = position;
+                }
+                visibility.set_if_neq(Visibility::Inherited);
+            }
+            None => {
+                visibility.set_if_neq(Visibility::Hidden);
+            }
+        }
+    }
+}
+
+fn place_map_items(
+    view: Res<MapView>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut items: Query<(&MapItem, &mut Node)>,
+) {
+    let Ok(window) = windows.single() else {
+        return;
+    };
+    let screen_center = window.size() / 2.0;
+    for (item, mut node) in &mut items {
+        let size = (item.radius * 2.0 * view.scale).max(item.min_size);
+        let position = view.to_screen(screen_center, item.position);
+        node.left = Val::Px(position.x - size / 2.0);
+        node.top = Val::Px(position.y - size / 2.0);
+        node.width = Val::Px(size);
+        node.height = Val::Px(size);
+    }
+}
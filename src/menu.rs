@@ -5,12 +5,24 @@ use bevy::{
     prelude::*,
 };
 
-use super::{DisplayQuality, GameState, TEXT_COLOR, Volume, despawn_screen};
+use super::{
+    DisplayQuality, GameState, MusicVolume, SfxVolume, TEXT_COLOR, Volume,
+    controls::{Action, Bindings, button_label, key_label},
+    defense::GameMode,
+    despawn_screen,
+    save::{AUTOSAVE_SLOT, MANUAL_SLOTS, SaveSlots, load_game, read_slot, slot_label},
+    session::reset_game,
+    ship::movement::{FlightModel, RetroThrust},
+};
 
-// This plugin manages the menu, with 5 different screens:
-// - a main menu with "New Game", "Settings", "Quit"
-// - a settings menu with two submenus and a back button
-// - two settings screen with a setting that can be set and a back button
+// This plugin manages the menu, with 8 different screens:
+// - a main menu with "Continue", "New Game", "Load Game", "Settings", "Quit"
+// - a load game screen listing the save slots
+// - a new game screen to choose the game mode and flight model before launching
+// - a settings menu with three submenus and a back button
+// - a display settings screen and a sound settings screen with the master, music and
+//   effects volumes
+// - a controls screen to rebind the keyboard and gamepad
 pub fn menu_plugin(app: &mut App) {
     app
         // At start, the menu is not enabled. This will be changed in `menu_setup` when
@@ -21,6 +33,27 @@ pub fn menu_plugin(app: &mut App) {
         // Systems to handle the main menu screen
         .add_systems(OnEnter(MenuState::Main), main_menu_setup)
         .add_systems(OnExit(MenuState::Main), despawn_screen::<OnMainMenuScreen>)
+        // Systems to handle the load game screen
+        .add_systems(OnEnter(MenuState::LoadGame), load_game_menu_setup)
+        .add_systems(
+            OnExit(MenuState::LoadGame),
+            despawn_screen::<OnLoadGameScreen>,
+        )
+        // Systems to handle the new game screen
+        .add_systems(OnEnter(MenuState::NewGame), new_game_menu_setup)
+        .add_systems(
+            Update,
+            (
+                setting_button::<GameMode>,
+                setting_button::<FlightModel>,
+                setting_button::<RetroThrust>,
+            )
+                .run_if(in_state(MenuState::NewGame)),
+        )
+        .add_systems(
+            OnExit(MenuState::NewGame),
+            despawn_screen::<OnNewGameScreen>,
+        )
         // Systems to handle the settings menu screen
         .add_systems(OnEnter(MenuState::Settings), settings_menu_setup)
         .add_systems(
@@ -44,26 +77,62 @@ pub fn menu_plugin(app: &mut App) {
         .add_systems(OnEnter(MenuState::SettingsSound), sound_settings_menu_setup)
         .add_systems(
             Update,
-            setting_button::<Volume>.run_if(in_state(MenuState::SettingsSound)),
+            (
+                setting_button::<Volume>,
+                setting_button::<MusicVolume>,
+                setting_button::<SfxVolume>,
+            )
+                .run_if(in_state(MenuState::SettingsSound)),
         )
         .add_systems(
             OnExit(MenuState::SettingsSound),
             despawn_screen::<OnSoundSettingsMenuScreen>,
         )
+        // Systems to handle the controls settings screen
+        .init_resource::<Rebinding>()
+        .add_systems(
+            OnEnter(MenuState::SettingsControls),
+            controls_settings_menu_setup,
+        )
+        .add_systems(
+            Update,
+            (
+                binding_button,
+                capture_binding,
+                update_binding_labels.run_if(
+                    resource_changed::<Bindings>
+                        .or(resource_changed::<Rebinding>)
+                        .or(any_match_filter::<Added<BindingButton>>),
+                ),
+            )
+                .chain()
+                .run_if(in_state(MenuState::SettingsControls)),
+        )
+        .add_systems(
+            OnExit(MenuState::SettingsControls),
+            (
+                despawn_screen::<OnControlsSettingsMenuScreen>,
+                |mut rebinding: ResMut<Rebinding>| rebinding.0 = None,
+            ),
+        )
         // Common systems to all screens that handles buttons behavior
         .add_systems(
             Update,
-            (menu_action, button_system).run_if(in_state(GameState::Menu)),
+            (menu_action, button_system).run_if(not(in_state(MenuState::Disabled))),
         );
 }
 
-// State used for the current menu screen
+// State used for the current menu screen. The settings screens are also shown over the
+// game while it is paused.
 #[derive(Clone, Copy, Default, Eq, PartialEq, Debug, Hash, States)]
-enum MenuState {
+pub enum MenuState {
     Main,
+    NewGame,
+    LoadGame,
     Settings,
     SettingsDisplay,
     SettingsSound,
+    SettingsControls,
     #[default]
     Disabled,
 }
@@ -72,6 +141,14 @@ enum MenuState {
 #[derive(Component)]
 struct OnMainMenuScreen;
 
+// Tag component used to tag entities added on the load game screen
+#[derive(Component)]
+struct OnLoadGameScreen;
+
+// Tag component used to tag entities added on the new game screen
+#[derive(Component)]
+struct OnNewGameScreen;
+
 // Tag component used to tag entities added on the settings menu screen
 #[derive(Component)]
 struct OnSettingsMenuScreen;
@@ -84,6 +161,10 @@ struct OnDisplaySettingsMenuScreen;
 #[derive(Component)]
 struct OnSoundSettingsMenuScreen;
 
+// Tag component used to tag entities added on the controls settings menu screen
+#[derive(Component)]
+struct OnControlsSettingsMenuScreen;
+
 const NORMAL_BUTTON: Color = Color::srgb(0.15, 0.15, 0.15);
 const HOVERED_BUTTON: Color = Color::srgb(0.25, 0.25, 0.25);
 const HOVERED_PRESSED_BUTTON: Color = Color::srgb(0.25, 0.65, 0.25);
@@ -96,16 +177,40 @@ struct SelectedOption;
 // All actions that can be triggered from a button click
 #[derive(Component)]
 enum MenuButtonAction {
+    Continue,
+    NewGame,
+    LoadGame,
+    LoadSlot(usize),
     Play,
     Settings,
     SettingsDisplay,
     SettingsSound,
+    SettingsControls,
+    ResetControls,
     BackToMainMenu,
     BackToSettings,
     Quit,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum InputDevice {
+    Keyboard,
+    Gamepad,
+}
+
+// A button on the controls screen showing what an action is bound to on one device
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+struct BindingButton {
+    action: Action,
+    device: InputDevice,
+}
+
+// The binding waiting for the player to press a key or gamepad button, if any
+#[derive(Resource, Default)]
+struct Rebinding(Option<BindingButton>);
+
 // This system handles changing all buttons color based on mouse interaction
+#[allow(clippy::type_complexity)]
 fn button_system(
     mut interaction_query: Query<
         (&Interaction, &mut BackgroundColor, Option<&SelectedOption>),
@@ -123,10 +228,12 @@ fn button_system(
 }
 
 // This system updates the settings when a new value for a setting is selected, and marks
-// the button as the one currently selected
+// the button as the one currently selected. Only buttons of the same setting are
+// considered, so several settings can share a screen.
+#[allow(clippy::type_complexity)]
 fn setting_button<T: Resource + Component + PartialEq + Copy>(
     interaction_query: Query<(&Interaction, &T, Entity), (Changed<Interaction>, With<Button>)>,
-    selected_query: Single<(Entity, &mut BackgroundColor), With<SelectedOption>>,
+    selected_query: Single<(Entity, &mut BackgroundColor), (With<SelectedOption>, With<T>)>,
     mut commands: Commands,
     mut setting: ResMut<T>,
 ) {
@@ -145,14 +252,18 @@ fn menu_setup(mut menu_state: ResMut<NextState<MenuState>>) {
     menu_state.set(MenuState::Main);
 }
 
-fn main_menu_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+fn main_menu_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    save_slots: Res<SaveSlots>,
+) {
     // Common style for all buttons on the screen
     let menu_bg = asset_server.load("menu_bg.png");
 
     let button_node = Node {
         width: Val::Px(300.0),
         height: Val::Px(65.0),
-        margin: UiRect::all(Val::Px(20.0)),
+        margin: UiRect::all(Val::Px(12.0)),
         justify_content: JustifyContent::Center,
         align_items: AlignItems::Center,
         ..default()
@@ -229,15 +340,38 @@ fn main_menu_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
                             ..default()
                         },
                     ),
-                    // Display three buttons for each action available from the main menu:
+                    // Display a button for each action available from the main menu:
+                    // - continue, only when there is a save to continue from
                     // - new game
+                    // - load game
                     // - settings
                     // - quit
+                    (
+                        Button,
+                        Node {
+                            display: if save_slots.latest().is_some() {
+                                Display::Flex
+                            } else {
+                                Display::None
+                            },
+                            ..button_node.clone()
+                        },
+                        BackgroundColor(NORMAL_BUTTON),
+                        MenuButtonAction::Continue,
+                        children![
+                            (ImageNode::new(right_icon.clone()), button_icon_node.clone()),
+                            (
+                                Text::new("Continue"),
+                                button_text_font.clone(),
+                                TextColor(TEXT_COLOR),
+                            ),
+                        ]
+                    ),
                     (
                         Button,
                         button_node.clone(),
                         BackgroundColor(NORMAL_BUTTON),
-                        MenuButtonAction::Play,
+                        MenuButtonAction::NewGame,
                         children![
                             (ImageNode::new(right_icon), button_icon_node.clone()),
                             (
@@ -247,6 +381,17 @@ fn main_menu_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
                             ),
                         ]
                     ),
+                    (
+                        Button,
+                        button_node.clone(),
+                        BackgroundColor(NORMAL_BUTTON),
+                        MenuButtonAction::LoadGame,
+                        children![(
+                            Text::new("Load Game"),
+                            button_text_font.clone(),
+                            TextColor(TEXT_COLOR),
+                        )]
+                    ),
                     (
                         Button,
                         button_node.clone(),
@@ -277,6 +422,238 @@ fn main_menu_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
     ));
 }
 
+fn load_game_menu_setup(mut commands: Commands, save_slots: Res<SaveSlots>) {
+    let button_node = Node {
+        width: Val::Px(560.0),
+        height: Val::Px(65.0),
+        margin: UiRect::all(Val::Px(10.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let button_text_style = (
+        TextFont {
+            font_size: 28.0,
+            ..default()
+        },
+        TextColor(TEXT_COLOR),
+    );
+
+    // Empty slots are listed too, without an action
+    let slots: Vec<_> = [AUTOSAVE_SLOT]
+        .into_iter()
+        .chain(MANUAL_SLOTS)
+        .map(|slot| {
+            let description = save_slots
+                .get(slot)
+                .map_or("Empty".to_string(), |summary| summary.describe());
+            (
+                slot,
+                save_slots.get(slot).is_some(),
+                format!("{}: {description}", slot_label(slot)),
+            )
+        })
+        .collect();
+    let slot_button_node = button_node.clone();
+    let slot_text_style = button_text_style.clone();
+    commands.spawn((
+        Node {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        OnLoadGameScreen,
+        children![(
+            Node {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(CRIMSON.into()),
+            Children::spawn((
+                SpawnWith(move |parent: &mut ChildSpawner| {
+                    for (slot, used, text) in slots {
+                        let mut entity = parent.spawn((
+                            Button,
+                            slot_button_node.clone(),
+                            BackgroundColor(NORMAL_BUTTON),
+                            children![(Text::new(text), slot_text_style.clone())],
+                        ));
+                        if used {
+                            entity.insert(MenuButtonAction::LoadSlot(slot));
+                        }
+                    }
+                }),
+                Spawn((
+                    Button,
+                    Node {
+                        width: Val::Px(200.0),
+                        ..button_node
+                    },
+                    BackgroundColor(NORMAL_BUTTON),
+                    MenuButtonAction::BackToMainMenu,
+                    children![(Text::new("Back"), button_text_style)],
+                )),
+            ))
+        )],
+    ));
+}
+
+fn new_game_menu_setup(
+    mut commands: Commands,
+    game_mode: Res<GameMode>,
+    flight_model: Res<FlightModel>,
+    retro_thrust: Res<RetroThrust>,
+) {
+    fn button_node() -> Node {
+        Node {
+            width: Val::Px(200.0),
+            height: Val::Px(65.0),
+            margin: UiRect::all(Val::Px(20.0)),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        }
+    }
+    fn button_text_style() -> impl Bundle {
+        (
+            TextFont {
+                font_size: 33.0,
+                ..default()
+            },
+            TextColor(TEXT_COLOR),
+        )
+    }
+
+    let game_mode = *game_mode;
+    let flight_model = *flight_model;
+    let retro_thrust = *retro_thrust;
+    commands.spawn((
+        Node {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        OnNewGameScreen,
+        children![(
+            Node {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(CRIMSON.into()),
+            children![
+                (
+                    Node {
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    Children::spawn((
+                        Spawn((Text::new("Mode"), button_text_style())),
+                        SpawnWith(move |parent: &mut ChildSpawner| {
+                            for mode_setting in [GameMode::Exploration, GameMode::ZDefense] {
+                                let mut entity = parent.spawn((
+                                    Button,
+                                    button_node(),
+                                    BackgroundColor(NORMAL_BUTTON),
+                                    mode_setting,
+                                    children![(
+                                        Text::new(mode_setting.label()),
+                                        button_text_style(),
+                                    )],
+                                ));
+                                if game_mode == mode_setting {
+                                    entity.insert(SelectedOption);
+                                }
+                            }
+                        })
+                    ))
+                ),
+                (
+                    Node {
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    Children::spawn((
+                        Spawn((Text::new("Flight Model"), button_text_style())),
+                        SpawnWith(move |parent: &mut ChildSpawner| {
+                            for model_setting in [FlightModel::Arcade, FlightModel::Newtonian] {
+                                let mut entity = parent.spawn((
+                                    Button,
+                                    button_node(),
+                                    BackgroundColor(NORMAL_BUTTON),
+                                    model_setting,
+                                    children![(
+                                        Text::new(format!("{model_setting:?}")),
+                                        button_text_style(),
+                                    )],
+                                ));
+                                if flight_model == model_setting {
+                                    entity.insert(SelectedOption);
+                                }
+                            }
+                        })
+                    ))
+                ),
+                (
+                    Node {
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    Children::spawn((
+                        Spawn((Text::new("Retro Thrust"), button_text_style())),
+                        SpawnWith(move |parent: &mut ChildSpawner| {
+                            for (retro_setting, label) in
+                                [(RetroThrust(true), "On"), (RetroThrust(false), "Off")]
+                            {
+                                let mut entity = parent.spawn((
+                                    Button,
+                                    Node {
+                                        width: Val::Px(120.0),
+                                        ..button_node()
+                                    },
+                                    BackgroundColor(NORMAL_BUTTON),
+                                    retro_setting,
+                                    children![(Text::new(label), button_text_style())],
+                                ));
+                                if retro_thrust == retro_setting {
+                                    entity.insert(SelectedOption);
+                                }
+                            }
+                        })
+                    ))
+                ),
+                (
+                    Node {
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    children![
+                        (
+                            Button,
+                            button_node(),
+                            BackgroundColor(NORMAL_BUTTON),
+                            MenuButtonAction::BackToMainMenu,
+                            children![(Text::new("Back"), button_text_style())]
+                        ),
+                        (
+                            Button,
+                            button_node(),
+                            BackgroundColor(NORMAL_BUTTON),
+                            MenuButtonAction::Play,
+                            children![(Text::new("Launch"), button_text_style())]
+                        ),
+                    ]
+                ),
+            ]
+        )],
+    ));
+}
+
 fn settings_menu_setup(mut commands: Commands) {
     let button_node = Node {
         width: Val::Px(200.0),
@@ -315,6 +692,7 @@ fn settings_menu_setup(mut commands: Commands) {
                 [
                     (MenuButtonAction::SettingsDisplay, "Display"),
                     (MenuButtonAction::SettingsSound, "Sound"),
+                    (MenuButtonAction::SettingsControls, "Controls"),
                     (MenuButtonAction::BackToMainMenu, "Back"),
                 ]
                 .into_iter()
@@ -422,25 +800,125 @@ fn display_settings_menu_setup(mut commands: Commands, display_quality: Res<Disp
     ));
 }
 
-fn sound_settings_menu_setup(mut commands: Commands, volume: Res<Volume>) {
+fn sound_settings_menu_setup(
+    mut commands: Commands,
+    volume: Res<Volume>,
+    music_volume: Res<MusicVolume>,
+    sfx_volume: Res<SfxVolume>,
+) {
+    fn button_node() -> Node {
+        Node {
+            width: Val::Px(200.0),
+            height: Val::Px(65.0),
+            margin: UiRect::all(Val::Px(20.0)),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        }
+    }
+    fn button_text_style() -> impl Bundle {
+        (
+            TextFont {
+                font_size: 33.0,
+                ..default()
+            },
+            TextColor(TEXT_COLOR),
+        )
+    }
+    // A label, then one button per volume level of the setting
+    fn volume_row<T: Component + PartialEq + Copy>(
+        label: &'static str,
+        current: T,
+        level: fn(u32) -> T,
+    ) -> impl Bundle {
+        (
+            Node {
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(CRIMSON.into()),
+            Children::spawn((
+                Spawn((
+                    Node {
+                        width: Val::Px(140.0),
+                        ..default()
+                    },
+                    Text::new(label),
+                    button_text_style(),
+                )),
+                SpawnWith(move |parent: &mut ChildSpawner| {
+                    for volume_setting in [0, 1, 2, 3, 4, 5, 6, 7, 8, 9] {
+                        let mut entity = parent.spawn((
+                            Button,
+                            Node {
+                                width: Val::Px(30.0),
+                                height: Val::Px(65.0),
+                                ..button_node()
+                            },
+                            BackgroundColor(NORMAL_BUTTON),
+                            level(volume_setting),
+                        ));
+                        if current == level(volume_setting) {
+                            entity.insert(SelectedOption);
+                        }
+                    }
+                }),
+            )),
+        )
+    }
+
+    commands.spawn((
+        Node {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        OnSoundSettingsMenuScreen,
+        children![(
+            Node {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(CRIMSON.into()),
+            children![
+                volume_row("Master", *volume, Volume),
+                volume_row("Music", *music_volume, MusicVolume),
+                volume_row("Effects", *sfx_volume, SfxVolume),
+                (
+                    Button,
+                    button_node(),
+                    BackgroundColor(NORMAL_BUTTON),
+                    MenuButtonAction::BackToSettings,
+                    children![(Text::new("Back"), button_text_style())]
+                )
+            ]
+        )],
+    ));
+}
+
+fn controls_settings_menu_setup(mut commands: Commands) {
+    // Rows are smaller than on the other screens, to fit every action
     let button_node = Node {
-        width: Val::Px(200.0),
-        height: Val::Px(65.0),
-        margin: UiRect::all(Val::Px(20.0)),
+        width: Val::Px(220.0),
+        height: Val::Px(34.0),
+        margin: UiRect::axes(Val::Px(10.0), Val::Px(3.0)),
         justify_content: JustifyContent::Center,
         align_items: AlignItems::Center,
         ..default()
     };
     let button_text_style = (
         TextFont {
-            font_size: 33.0,
+            font_size: 24.0,
             ..default()
         },
         TextColor(TEXT_COLOR),
     );
 
-    let volume = *volume;
-    let button_node_clone = button_node.clone();
+    let row_button_node = button_node.clone();
+    let row_text_style = button_text_style.clone();
     commands.spawn((
         Node {
             width: Val::Percent(100.0),
@@ -449,54 +927,162 @@ fn sound_settings_menu_setup(mut commands: Commands, volume: Res<Volume>) {
             justify_content: JustifyContent::Center,
             ..default()
         },
-        OnSoundSettingsMenuScreen,
+        OnControlsSettingsMenuScreen,
         children![(
             Node {
                 flex_direction: FlexDirection::Column,
                 align_items: AlignItems::Center,
+                padding: UiRect::all(Val::Px(10.0)),
                 ..default()
             },
             BackgroundColor(CRIMSON.into()),
-            children![
-                (
-                    Node {
-                        align_items: AlignItems::Center,
+            Children::spawn((
+                Spawn((
+                    Text::new(
+                        "Click a binding, then press the new key or button (click again to cancel)"
+                    ),
+                    TextFont {
+                        font_size: 18.0,
                         ..default()
                     },
-                    BackgroundColor(CRIMSON.into()),
-                    Children::spawn((
-                        Spawn((Text::new("Volume"), button_text_style.clone())),
-                        SpawnWith(move |parent: &mut ChildSpawner| {
-                            for volume_setting in [0, 1, 2, 3, 4, 5, 6, 7, 8, 9] {
-                                let mut entity = parent.spawn((
-                                    Button,
+                    TextColor(TEXT_COLOR),
+                )),
+                // One row per action: its name, then the keyboard and gamepad bindings
+                SpawnWith(move |parent: &mut ChildSpawner| {
+                    for action in Action::ALL {
+                        parent
+                            .spawn(Node {
+                                align_items: AlignItems::Center,
+                                ..default()
+                            })
+                            .with_children(|row| {
+                                row.spawn((
                                     Node {
-                                        width: Val::Px(30.0),
-                                        height: Val::Px(65.0),
-                                        ..button_node_clone.clone()
+                                        width: Val::Px(160.0),
+                                        ..default()
                                     },
-                                    BackgroundColor(NORMAL_BUTTON),
-                                    Volume(volume_setting),
+                                    Text::new(action.label()),
+                                    row_text_style.clone(),
                                 ));
-                                if volume == Volume(volume_setting) {
-                                    entity.insert(SelectedOption);
+                                for device in [InputDevice::Keyboard, InputDevice::Gamepad] {
+                                    // The labels are filled in by `update_binding_labels`
+                                    row.spawn((
+                                        Button,
+                                        row_button_node.clone(),
+                                        BackgroundColor(NORMAL_BUTTON),
+                                        BindingButton { action, device },
+                                        children![(Text::default(), row_text_style.clone())],
+                                    ));
                                 }
-                            }
+                            });
+                    }
+                }),
+                Spawn((
+                    Node {
+                        margin: UiRect::top(Val::Px(10.0)),
+                        ..default()
+                    },
+                    Children::spawn(SpawnIter(
+                        [
+                            (MenuButtonAction::ResetControls, "Reset to defaults"),
+                            (MenuButtonAction::BackToSettings, "Back"),
+                        ]
+                        .into_iter()
+                        .map(move |(action, text)| {
+                            (
+                                Button,
+                                button_node.clone(),
+                                BackgroundColor(NORMAL_BUTTON),
+                                action,
+                                children![(Text::new(text), button_text_style.clone())],
+                            )
                         })
                     ))
-                ),
-                (
-                    Button,
-                    button_node,
-                    BackgroundColor(NORMAL_BUTTON),
-                    MenuButtonAction::BackToSettings,
-                    children![(Text::new("Back"), button_text_style)]
-                )
-            ]
+                )),
+            ))
         )],
     ));
 }
 
+// Clicking a binding waits for the next key or gamepad button to bind to it, clicking it
+// again cancels. Any key can be bound that way, Escape included.
+#[allow(clippy::type_complexity)]
+fn binding_button(
+    interaction_query: Query<(&Interaction, &BindingButton), (Changed<Interaction>, With<Button>)>,
+    mut rebinding: ResMut<Rebinding>,
+) {
+    for (interaction, binding) in &interaction_query {
+        if *interaction == Interaction::Pressed {
+            rebinding.0 = if rebinding.0 == Some(*binding) {
+                None
+            } else {
+                Some(*binding)
+            };
+        }
+    }
+}
+
+fn capture_binding(
+    mut rebinding: ResMut<Rebinding>,
+    mut bindings: ResMut<Bindings>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+) {
+    let Some(BindingButton { action, device }) = rebinding.0 else {
+        return;
+    };
+    match device {
+        InputDevice::Keyboard => {
+            let Some(key) = keyboard_input.get_just_pressed().next() else {
+                return;
+            };
+            bindings.bind_key(action, *key);
+        }
+        InputDevice::Gamepad => {
+            let Some(button) = gamepads
+                .iter()
+                .find_map(|gamepad| gamepad.get_just_pressed().next())
+            else {
+                return;
+            };
+            bindings.bind_button(action, *button);
+        }
+    }
+    rebinding.0 = None;
+}
+
+fn update_binding_labels(
+    bindings: Res<Bindings>,
+    rebinding: Res<Rebinding>,
+    buttons: Query<(&BindingButton, &Children)>,
+    mut texts: Query<&mut Text>,
+) {
+    for (binding, children) in &buttons {
+        let Some(mut text) = children
+            .first()
+            .and_then(|child| texts.get_mut(*child).ok())
+        else {
+            continue;
+        };
+        text.0 = if rebinding.0 == Some(*binding) {
+            "Press...".to_string()
+        } else {
+            let label = match binding.device {
+                InputDevice::Keyboard => bindings
+                    .keyboard
+                    .get(&binding.action)
+                    .map(|key| key_label(*key)),
+                InputDevice::Gamepad => bindings
+                    .gamepad
+                    .get(&binding.action)
+                    .map(|button| button_label(*button)),
+            };
+            label.unwrap_or_else(|| "-".to_string())
+        };
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 fn menu_action(
     interaction_query: Query<
         (&Interaction, &MenuButtonAction),
@@ -505,14 +1091,30 @@ fn menu_action(
     mut app_exit_events: EventWriter<AppExit>,
     mut menu_state: ResMut<NextState<MenuState>>,
     mut game_state: ResMut<NextState<GameState>>,
+    current_game_state: Res<State<GameState>>,
+    mut bindings: ResMut<Bindings>,
+    save_slots: Res<SaveSlots>,
+    mut commands: Commands,
 ) {
     for (interaction, menu_button_action) in &interaction_query {
         if *interaction == Interaction::Pressed {
             match menu_button_action {
+                // Continuing loads the slot saved to last
+                MenuButtonAction::Continue => {
+                    if let Some(slot) = save_slots.latest() {
+                        load_slot(slot, &mut commands, &mut game_state, &mut menu_state);
+                    }
+                }
+                MenuButtonAction::LoadSlot(slot) => {
+                    load_slot(*slot, &mut commands, &mut game_state, &mut menu_state);
+                }
                 MenuButtonAction::Quit => {
                     app_exit_events.write(AppExit::Success);
                 }
+                MenuButtonAction::NewGame => menu_state.set(MenuState::NewGame),
+                MenuButtonAction::LoadGame => menu_state.set(MenuState::LoadGame),
                 MenuButtonAction::Play => {
+                    reset_game(&mut commands);
                     game_state.set(GameState::Space);
                     menu_state.set(MenuState::Disabled);
                 }
@@ -523,7 +1125,20 @@ fn menu_action(
                 MenuButtonAction::SettingsSound => {
                     menu_state.set(MenuState::SettingsSound);
                 }
-                MenuButtonAction::BackToMainMenu => menu_state.set(MenuState::Main),
+                MenuButtonAction::SettingsControls => {
+                    menu_state.set(MenuState::SettingsControls);
+                }
+                MenuButtonAction::ResetControls => {
+                    *bindings = Bindings::default();
+                }
+                // Settings opened from the pause overlay go back to it
+                MenuButtonAction::BackToMainMenu => {
+                    if *current_game_state.get() == GameState::Menu {
+                        menu_state.set(MenuState::Main);
+                    } else {
+                        menu_state.set(MenuState::Disabled);
+                    }
+                }
                 MenuButtonAction::BackToSettings => {
                     menu_state.set(MenuState::Settings);
                 }
@@ -531,3 +1146,18 @@ fn menu_action(
         }
     }
 }
+
+// Starts playing the game saved in `slot`. A save that can't be read leaves the menu as
+// it is, the reason is in the log.
+fn load_slot(
+    slot: usize,
+    commands: &mut Commands,
+    game_state: &mut NextState<GameState>,
+    menu_state: &mut NextState<MenuState>,
+) {
+    if let Some(save) = read_slot(slot) {
+        load_game(commands, save);
+        game_state.set(GameState::Space);
+        menu_state.set(MenuState::Disabled);
+    }
+}
@@ -0,0 +1,350 @@
+// Arrows at the edge of the screen pointing at navigation points out of view, with their
+// name and distance. The Next Target action cycles the selected point, whose arrow stands out,
+// and the Set Waypoint action puts the autopilot's waypoint on it. A right click sets the
+// waypoint where it points in space.
+use crate::GameState;
+use crate::camera::move_camera;
+use crate::controls::{Action, ActionState};
+use crate::pause::PauseState;
+use crate::ship::control::{ControlSet, PlayerControlled};
+use crate::space::OnSpaceScreen;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+
+// Size of an indicator, an arrow above a name and a distance
+const INDICATOR_WIDTH: f32 = 120.0;
+const INDICATOR_HEIGHT: f32 = 56.0;
+// Gap between the indicators and the edge of the screen
+const EDGE_MARGIN: f32 = 8.0;
+// Distances are shown rounded to this many units, so that the text is not laid out again
+// every frame
+const DISTANCE_STEP: f32 = 10.0;
+const INDICATOR_COLOR: Color = Color::srgba(0.9, 0.9, 0.9, 0.6);
+const SELECTED_COLOR: Color = Color::srgb(1.0, 0.85, 0.2);
+// Size of the marker of a waypoint set on a location, in world units
+const WAYPOINT_MARKER_SIZE: f32 = 40.0;
+
+pub fn nav_plugin(app: &mut App) {
+    app.init_resource::<SelectedNavPoint>()
+        .add_event::<WaypointRequest>()
+        .add_systems(OnEnter(GameState::Space), clear_selection)
+        .add_systems(
+            Update,
+            (
+                cycle_nav_target,
+                request_waypoint.after(cycle_nav_target),
+                click_to_set_waypoint,
+                add_indicators,
+                update_indicators
+                    .after(cycle_nav_target)
+                    .after(add_indicators)
+                    .after(move_camera),
+            )
+                .run_if(in_state(PauseState::Running)),
+        )
+        // Waypoints are also set from the system map, while the game is frozen
+        .add_systems(
+            Update,
+            (
+                set_waypoint.run_if(on_event::<WaypointRequest>),
+                follow_waypoints,
+            )
+                .chain()
+                .after(request_waypoint)
+                .after(click_to_set_waypoint)
+                .before(ControlSet::Intent)
+                .run_if(in_state(GameState::Space)),
+        );
+}
+
+// Somewhere the player may want to find their way to
+#[derive(Component, Debug, Clone)]
+pub struct NavPoint {
+    pub label: String,
+}
+
+// The navigation point picked with the Next Target action, if any
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SelectedNavPoint(pub Option<Entity>);
+
+// Where the autopilot flies to. There is at most one, following the body it was set on
+// as it orbits, or sitting still at a location.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Waypoint {
+    pub follow: Option<Entity>,
+}
+
+// Replaces the waypoint
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub enum WaypointRequest {
+    Follow(Entity),
+    At(Vec2),
+    Clear,
+}
+
+// The indicator of a `NavPoint`
+#[derive(Component)]
+struct NavIndicator(Entity);
+
+#[derive(Component)]
+struct IndicatorArrow;
+
+#[derive(Component)]
+struct IndicatorLabel;
+
+// The points of the last visit to space are gone
+fn clear_selection(mut selected: ResMut<SelectedNavPoint>) {
+    selected.0 = None;
+}
+
+// Goes through the points in alphabetical order, then back to no selection
+fn cycle_nav_target(
+    actions: Res<ActionState>,
+    mut selected: ResMut<SelectedNavPoint>,
+    points: Query<(Entity, &NavPoint)>,
+) {
+    if !actions.just_pressed(Action::NextTarget) {
+        return;
+    }
+    let mut points: Vec<_> = points.iter().collect();
+    points.sort_by(|(a, a_point), (b, b_point)| a_point.label.cmp(&b_point.label).then(a.cmp(b)));
+    let next = match selected.0 {
+        Some(current) => points
+            .iter()
+            .skip_while(|(entity, _)| *entity != current)
+            .nth(1),
+        None => points.first(),
+    };
+    selected.0 = next.map(|(entity, _)| *entity);
+}
+
+// Sets the waypoint on the selected point, or takes it away if it is already there
+fn request_waypoint(
+    actions: Res<ActionState>,
+    selected: Res<SelectedNavPoint>,
+    waypoints: Query<(Entity, &Waypoint)>,
+    mut requests: EventWriter<WaypointRequest>,
+) {
+    if !actions.just_pressed(Action::SetWaypoint) {
+        return;
+    }
+    let already_set = waypoints
+        .iter()
+        .any(|(entity, waypoint)| selected.0 == Some(entity) || selected.0 == waypoint.follow);
+    match selected.0 {
+        Some(point) if !already_set => requests.write(WaypointRequest::Follow(point)),
+        _ => requests.write(WaypointRequest::Clear),
+    };
+}
+
+fn click_to_set_waypoint(
+    mouse: Res<ButtonInput<MouseButton>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
+    mut requests: EventWriter<WaypointRequest>,
+) {
+    if !mouse.just_pressed(MouseButton::Right) {
+        return;
+    }
+    let (Ok(window), Ok((camera, camera_transform))) = (windows.single(), cameras.single()) else {
+        return;
+    };
+    if let Some(location) = window
+        .cursor_position()
+        .and_then(|cursor| camera.viewport_to_world_2d(camera_transform, cursor).ok())
+    {
+        requests.write(WaypointRequest::At(location));
+    }
+}
+
+// Only the last request counts, the waypoint it sets is selected
+fn set_waypoint(
+    mut commands: Commands,
+    mut requests: EventReader<WaypointRequest>,
+    mut selected: ResMut<SelectedNavPoint>,
+    waypoints: Query<Entity, With<Waypoint>>,
+    points: Query<&Transform>,
+) {
+    let Some(request) = requests.read().last() else {
+        return;
+    };
+    for waypoint in &waypoints {
+        commands.entity(waypoint).despawn();
+        if selected.0 == Some(waypoint) {
+            selected.0 = None;
+        }
+    }
+    match *request {
+        WaypointRequest::Follow(point) => {
+            let Ok(transform) = points.get(point) else {
+                return;
+            };
+            commands.spawn((
+                Waypoint {
+                    follow: Some(point),
+                },
+                Transform::from_translation(transform.translation),
+                OnSpaceScreen,
+            ));
+            selected.0 = Some(point);
+        }
+        WaypointRequest::At(location) => {
+            // A location has no body to show it, so the waypoint gets a marker of its own
+            let waypoint = commands
+                .spawn((
+                    Waypoint { follow: None },
+                    NavPoint {
+                        label: "Waypoint".to_string(),
+                    },
+                    Sprite::from_color(SELECTED_COLOR, Vec2::splat(WAYPOINT_MARKER_SIZE)),
+                    Transform::from_translation(location.extend(-0.5))
+                        .with_rotation(Quat::from_rotation_z(std::f32::consts::FRAC_PI_4)),
+                    OnSpaceScreen,
+                ))
+                .id();
+            selected.0 = Some(waypoint);
+        }
+        WaypointRequest::Clear => {}
+    }
+}
+
+// Keeps waypoints on the bodies they follow, and drops those whose body is gone
+fn follow_waypoints(
+    mut commands: Commands,
+    mut waypoints: Query<(Entity, &Waypoint, &mut Transform)>,
+    bodies: Query<&Transform, Without<Waypoint>>,
+) {
+    for (entity, waypoint, mut transform) in &mut waypoints {
+        let Some(follow) = waypoint.follow else {
+            continue;
+        };
+        match bodies.get(follow) {
+            Ok(body) => transform.translation = body.translation,
+            Err(_) => commands.entity(entity).despawn(),
+        }
+    }
+}
+
+fn add_indicators(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    points: Query<Entity, Added<NavPoint>>,
+) {
+    for point in &points {
+        commands.spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Px(INDICATOR_WIDTH),
+                height: Val::Px(INDICATOR_HEIGHT),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            // Placed by `update_indicators`
+            Visibility::Hidden,
+            NavIndicator(point),
+            OnSpaceScreen,
+            children![
+                (
+                    Text::new("\u{2192}"),
+                    TextFont {
+                        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                        font_size: 24.0,
+                        ..default()
+                    },
+                    TextColor(INDICATOR_COLOR),
+                    IndicatorArrow,
+                ),
+                (
+                    Text::default(),
+                    TextFont {
+                        font_size: 12.0,
+                        ..default()
+                    },
+                    TextColor(INDICATOR_COLOR),
+                    TextLayout::new_with_justify(JustifyText::Center),
+                    IndicatorLabel,
+                ),
+            ],
+        ));
+    }
+}
+
+// Places each indicator where the line from the middle of the screen to its point
+// leaves the screen, and hides the indicators of the points in view
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn update_indicators(
+    mut commands: Commands,
+    selected: Res<SelectedNavPoint>,
+    cameras: Query<(&Camera, &Transform), With<Camera2d>>,
+    ships: Query<&Transform, With<PlayerControlled>>,
+    points: Query<(&Transform, &NavPoint)>,
+    mut indicators: Query<(Entity, &NavIndicator, &mut Node, &mut Visibility, &Children)>,
+    mut arrows: Query<
+        (&mut Transform, &mut TextColor),
+        (
+            With<IndicatorArrow>,
+            Without<Camera2d>,
+            Without<PlayerControlled>,
+            Without<NavPoint>,
+        ),
+    >,
+    mut labels: Query<(&mut Text, &mut TextColor), (With<IndicatorLabel>, Without<IndicatorArrow>)>,
+) {
+    let (Ok((camera, camera_transform)), Ok(ship)) = (cameras.single(), ships.single()) else {
+        return;
+    };
+    let Some(viewport) = camera.logical_viewport_rect() else {
+        return;
+    };
+    // The camera has just moved, its global transform is only updated at the
+    // end of the frame
+    let camera_transform = GlobalTransform::from(*camera_transform);
+    let center = viewport.center();
+    let half_size = viewport.half_size() - Vec2::new(INDICATOR_WIDTH, INDICATOR_HEIGHT) / 2.0;
+    let inner = Rect::from_center_half_size(center, half_size - EDGE_MARGIN);
+    for (indicator, NavIndicator(point), mut node, mut visibility, children) in &mut indicators {
+        let Ok((transform, nav_point)) = points.get(*point) else {
+            // The point is gone
+            commands.entity(indicator).despawn();
+            continue;
+        };
+        let position = transform.translation;
+        let Ok(on_screen) = camera.world_to_viewport(&camera_transform, position) else {
+            continue;
+        };
+        if viewport.contains(on_screen) {
+            visibility.set_if_neq(Visibility::Hidden);
+            continue;
+        }
+        visibility.set_if_neq(Visibility::Inherited);
+
+        // Viewport y grows downwards, and so does the arrow's rotation
+        let direction = on_screen - center;
+        let scale = (inner.half_size() / direction.abs()).min_element();
+        let anchor = center + direction * scale;
+        node.left = Val::Px(anchor.x - INDICATOR_WIDTH / 2.0);
+        node.top = Val::Px(anchor.y - INDICATOR_HEIGHT / 2.0);
+
+        let color = if selected.0 == Some(*point) {
+            SELECTED_COLOR
+        } else {
+            INDICATOR_COLOR
+        };
+        let distance = ship.translation.truncate().distance(position.truncate());
+        let distance = (distance / DISTANCE_STEP).round() * DISTANCE_STEP;
+        let label = format!("{}\n{distance:.0}", nav_point.label);
+        for child in children {
+            if let Ok((mut arrow, mut arrow_color)) = arrows.get_mut(*child) {
+                arrow.rotation = Quat::from_rotation_z(direction.y.atan2(direction.x));
+                arrow_color.set_if_neq(TextColor(color));
+            }
+            if let Ok((mut text, mut text_color)) = labels.get_mut(*child) {
+                if text.0 != label {
+                    text.0 = label.clone();
+                }
+                text_color.set_if_neq(TextColor(color));
+            }
+        }
+    }
+}
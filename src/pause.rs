@@ -0,0 +1,281 @@
+use bevy::{color::palettes::css::CRIMSON, ecs::spawn::SpawnIter, prelude::*};
+
+use super::{
+    GameState, TEXT_COLOR,
+    controls::{Action, ActionState},
+    despawn_screen,
+    menu::MenuState,
+    save::{MANUAL_SLOTS, SaveRequest, SaveSlots, slot_label},
+};
+
+// This plugin pauses the game while in space:
+// - the Pause action freezes the virtual clock and shows an overlay
+// - the overlay offers "Resume", "Settings" (the menu's settings screens), a row of save
+//   slots and "Main Menu"
+// Gameplay systems run in `PauseState::Running` instead of `GameState::Space`, so that
+// nothing moves or reacts to the controls while the game is paused or the system map is
+// open.
+pub fn pause_plugin(app: &mut App) {
+    app.add_sub_state::<PauseState>()
+        .add_systems(
+            Update,
+            toggle_pause.run_if(in_state(GameState::Space).and(in_state(MenuState::Disabled))),
+        )
+        .add_systems(OnEnter(PauseState::Paused), pause_setup)
+        .add_systems(
+            Update,
+            (
+                (pause_action, button_system).run_if(in_state(MenuState::Disabled)),
+                // The overlay steps aside while a settings screen is shown
+                show_overlay.run_if(state_changed::<MenuState>),
+                update_save_status.run_if(
+                    resource_changed::<SaveSlots>.or(any_match_filter::<Added<SaveStatus>>),
+                ),
+            )
+                .run_if(in_state(PauseState::Paused)),
+        )
+        .add_systems(
+            OnExit(PauseState::Paused),
+            (despawn_screen::<OnPauseScreen>, resume_time),
+        );
+}
+
+// Whether the game is paused, only exists while in space
+#[derive(SubStates, Clone, Copy, Default, Eq, PartialEq, Debug, Hash)]
+#[source(GameState = GameState::Space)]
+pub enum PauseState {
+    #[default]
+    Running,
+    Paused,
+    // The system map is shown, see `map.rs`
+    Map,
+}
+
+// Tag component used to tag entities added on the pause overlay
+#[derive(Component)]
+struct OnPauseScreen;
+
+// Tag component for the line telling which slot was saved to last
+#[derive(Component)]
+struct SaveStatus;
+
+// All actions that can be triggered from a button click on the pause overlay
+#[derive(Component, Clone, Copy)]
+enum PauseButtonAction {
+    Resume,
+    Settings,
+    Save(usize),
+    BackToMainMenu,
+}
+
+const NORMAL_BUTTON: Color = Color::srgb(0.15, 0.15, 0.15);
+const HOVERED_BUTTON: Color = Color::srgb(0.25, 0.25, 0.25);
+const PRESSED_BUTTON: Color = Color::srgb(0.35, 0.75, 0.35);
+
+fn toggle_pause(
+    actions: Res<ActionState>,
+    pause_state: Res<State<PauseState>>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+) {
+    if actions.just_pressed(Action::Pause) {
+        next_pause_state.set(match pause_state.get() {
+            PauseState::Running => PauseState::Paused,
+            // The Pause action closes the map rather than pausing over it
+            PauseState::Paused | PauseState::Map => PauseState::Running,
+        });
+    }
+}
+
+fn pause_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut time: ResMut<Time<Virtual>>,
+) {
+    time.pause();
+
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+    let button_node = Node {
+        width: Val::Px(250.0),
+        height: Val::Px(55.0),
+        margin: UiRect::all(Val::Px(10.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let button_text_style = (
+        TextFont {
+            font: font.clone(),
+            font_size: 30.0,
+            ..default()
+        },
+        TextColor(TEXT_COLOR),
+    );
+    let slot_button_node = Node {
+        width: Val::Px(110.0),
+        ..button_node.clone()
+    };
+    let slot_text_style = button_text_style.clone();
+
+    commands.spawn((
+        Node {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        BackgroundColor(Color::BLACK.with_alpha(0.6)),
+        // Above the HUD and the action menu
+        GlobalZIndex(10),
+        OnPauseScreen,
+        children![(
+            Node {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                padding: UiRect::all(Val::Px(20.0)),
+                ..default()
+            },
+            BackgroundColor(CRIMSON.into()),
+            children![
+                (
+                    Text::new("Paused"),
+                    TextFont {
+                        font: font.clone(),
+                        font_size: 60.0,
+                        ..default()
+                    },
+                    TextColor(TEXT_COLOR),
+                    Node {
+                        margin: UiRect::all(Val::Px(20.0)),
+                        ..default()
+                    },
+                ),
+                (
+                    Button,
+                    button_node.clone(),
+                    BackgroundColor(NORMAL_BUTTON),
+                    PauseButtonAction::Resume,
+                    children![(Text::new("Resume"), button_text_style.clone())],
+                ),
+                (
+                    Button,
+                    button_node.clone(),
+                    BackgroundColor(NORMAL_BUTTON),
+                    PauseButtonAction::Settings,
+                    children![(Text::new("Settings"), button_text_style.clone())],
+                ),
+                // One button per save slot
+                (
+                    Node {
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    Children::spawn(SpawnIter(MANUAL_SLOTS.into_iter().map(move |slot| {
+                        (
+                            Button,
+                            slot_button_node.clone(),
+                            BackgroundColor(NORMAL_BUTTON),
+                            PauseButtonAction::Save(slot),
+                            children![(Text::new(slot_label(slot)), slot_text_style.clone())],
+                        )
+                    }))),
+                ),
+                (
+                    Text::default(),
+                    TextFont {
+                        font,
+                        font_size: 20.0,
+                        ..default()
+                    },
+                    TextColor(TEXT_COLOR),
+                    SaveStatus,
+                ),
+                (
+                    Button,
+                    button_node,
+                    BackgroundColor(NORMAL_BUTTON),
+                    PauseButtonAction::BackToMainMenu,
+                    children![(Text::new("Main Menu"), button_text_style)],
+                ),
+            ]
+        )],
+    ));
+}
+
+fn resume_time(mut time: ResMut<Time<Virtual>>) {
+    time.unpause();
+}
+
+// This system handles changing all buttons color based on mouse interaction
+#[allow(clippy::type_complexity)]
+fn button_system(
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor),
+        (Changed<Interaction>, With<Button>),
+    >,
+) {
+    for (interaction, mut background_color) in &mut interaction_query {
+        *background_color = match *interaction {
+            Interaction::Pressed => PRESSED_BUTTON.into(),
+            Interaction::Hovered => HOVERED_BUTTON.into(),
+            Interaction::None => NORMAL_BUTTON.into(),
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn pause_action(
+    interaction_query: Query<
+        (&Interaction, &PauseButtonAction),
+        (Changed<Interaction>, With<Button>),
+    >,
+    mut pause_state: ResMut<NextState<PauseState>>,
+    mut menu_state: ResMut<NextState<MenuState>>,
+    mut game_state: ResMut<NextState<GameState>>,
+    mut save_requests: EventWriter<SaveRequest>,
+) {
+    for (interaction, action) in &interaction_query {
+        if *interaction == Interaction::Pressed {
+            match action {
+                PauseButtonAction::Resume => pause_state.set(PauseState::Running),
+                PauseButtonAction::Settings => menu_state.set(MenuState::Settings),
+                PauseButtonAction::Save(slot) => {
+                    save_requests.write(SaveRequest(*slot));
+                }
+                // Leaving space snapshots the session like docking does, and drops the
+                // pause state along with it
+                PauseButtonAction::BackToMainMenu => game_state.set(GameState::Menu),
+            }
+        }
+    }
+}
+
+fn show_overlay(
+    menu_state: Res<State<MenuState>>,
+    mut overlay: Query<&mut Visibility, With<OnPauseScreen>>,
+) {
+    for mut visibility in &mut overlay {
+        *visibility = if *menu_state.get() == MenuState::Disabled {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+fn update_save_status(save_slots: Res<SaveSlots>, mut status: Query<&mut Text, With<SaveStatus>>) {
+    let Ok(mut text) = status.single_mut() else {
+        return;
+    };
+    text.0 = match save_slots.latest() {
+        Some(slot) => format!(
+            "Last saved: {} ({})",
+            slot_label(slot),
+            save_slots
+                .get(slot)
+                .map(|summary| summary.describe())
+                .unwrap_or_default()
+        ),
+        None => "No saved game yet".to_string(),
+    };
+}
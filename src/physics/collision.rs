@@ -0,0 +1,317 @@
+use crate::physics::Velocity;
+use crate::planets::orbit::Orbit;
+use bevy::prelude::*;
+
+// Collision volume derived from the entity's sprite and scale
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Collider {
+    pub shape: ColliderShape,
+    // Fraction of the sprite that is solid, sprites usually have transparent margins
+    pub fill: f32,
+    // Half extents in world units, kept in sync with the sprite by `size_colliders`.
+    // Zero until the sprite's image is loaded.
+    pub half_extents: Vec2,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColliderShape {
+    // Circle fitting the smaller side of the sprite
+    Circle,
+    // Box aligned with the world axes, bounding the sprite as it rotates
+    Aabb,
+}
+
+impl Collider {
+    pub fn circle(fill: f32) -> Self {
+        Self {
+            shape: ColliderShape::Circle,
+            fill,
+            half_extents: Vec2::ZERO,
+        }
+    }
+
+    pub fn aabb(fill: f32) -> Self {
+        Self {
+            shape: ColliderShape::Aabb,
+            fill,
+            half_extents: Vec2::ZERO,
+        }
+    }
+
+    // Radius of the circle enclosing the collider
+    pub fn radius(&self) -> f32 {
+        match self.shape {
+            ColliderShape::Circle => self.half_extents.min_element(),
+            ColliderShape::Aabb => self.half_extents.length(),
+        }
+    }
+}
+
+// How a body reacts when something runs into it
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub enum CollisionResponse {
+    // Whatever hits it bounces off, keeping `restitution` of its impact speed
+    Bounce { restitution: f32 },
+    // Impacts slower than `safe_speed` come to rest on the surface, faster ones bounce
+    Land { safe_speed: f32 },
+    // Anything hitting it is destroyed
+    Crash,
+}
+
+// Restitution used when coming in too fast to land
+const HARD_LANDING_RESTITUTION: f32 = 0.3;
+
+// Sent for every freely moving collider overlapping another one
+#[derive(Event, Debug, Clone, Copy)]
+pub struct CollisionEvent {
+    pub entity: Entity,
+    pub other: Entity,
+    // Unit vector pointing from `other` towards `entity`
+    pub normal: Vec2,
+    // How deep the colliders overlap along the normal
+    pub depth: f32,
+    // Speed at which the two were closing in along the normal, 0.0 when separating
+    pub impact_speed: f32,
+}
+
+// Overlap between two colliders
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Contact {
+    // Unit vector pointing from the second collider towards the first
+    pub normal: Vec2,
+    pub depth: f32,
+}
+
+// Overlap test between two colliders at the given positions
+pub fn contact(a_position: Vec2, a: &Collider, b_position: Vec2, b: &Collider) -> Option<Contact> {
+    match (a.shape, b.shape) {
+        (ColliderShape::Circle, ColliderShape::Circle) => {
+            circle_circle(a_position, a.radius(), b_position, b.radius())
+        }
+        (ColliderShape::Circle, ColliderShape::Aabb) => {
+            circle_aabb(a_position, a.radius(), b_position, b.half_extents)
+        }
+        (ColliderShape::Aabb, ColliderShape::Circle) => {
+            circle_aabb(b_position, b.radius(), a_position, a.half_extents).map(|contact| Contact {
+                normal: -contact.normal,
+                ..contact
+            })
+        }
+        (ColliderShape::Aabb, ColliderShape::Aabb) => {
+            aabb_aabb(a_position, a.half_extents, b_position, b.half_extents)
+        }
+    }
+}
+
+fn circle_circle(a: Vec2, a_radius: f32, b: Vec2, b_radius: f32) -> Option<Contact> {
+    let offset = a - b;
+    let distance = offset.length();
+    let depth = a_radius + b_radius - distance;
+    (depth > 0.0).then(|| Contact {
+        normal: offset.try_normalize().unwrap_or(Vec2::Y),
+        depth,
+    })
+}
+
+fn circle_aabb(circle: Vec2, radius: f32, center: Vec2, half_extents: Vec2) -> Option<Contact> {
+    let local = circle - center;
+    let closest = local.clamp(-half_extents, half_extents);
+    if closest == local {
+        // The circle's center is inside the box: push out through the nearest side
+        let gap = half_extents - local.abs();
+        let normal = if gap.x < gap.y {
+            Vec2::new(local.x.signum(), 0.0)
+        } else {
+            Vec2::new(0.0, local.y.signum())
+        };
+        return Some(Contact {
+            normal,
+            depth: gap.min_element() + radius,
+        });
+    }
+    let offset = local - closest;
+    let distance = offset.length();
+    (distance < radius).then(|| Contact {
+        normal: offset / distance,
+        depth: radius - distance,
+    })
+}
+
+fn aabb_aabb(a: Vec2, a_half: Vec2, b: Vec2, b_half: Vec2) -> Option<Contact> {
+    let offset = a - b;
+    let overlap = a_half + b_half - offset.abs();
+    if overlap.x <= 0.0 || overlap.y <= 0.0 {
+        return None;
+    }
+    Some(if overlap.x < overlap.y {
+        Contact {
+            normal: Vec2::new(offset.x.signum(), 0.0),
+            depth: overlap.x,
+        }
+    } else {
+        Contact {
+            normal: Vec2::new(0.0, offset.y.signum()),
+            depth: overlap.y,
+        }
+    })
+}
+
+pub fn size_colliders(
+    mut query: Query<(&mut Collider, &Sprite, &Transform)>,
+    images: Res<Assets<Image>>,
+) {
+    for (mut collider, sprite, transform) in &mut query {
+        let size = sprite
+            .custom_size
+            .or_else(|| images.get(&sprite.image).map(|image| image.size_f32()));
+        let Some(size) = size else {
+            continue;
+        };
+        let mut half_extents = size * transform.scale.truncate().abs() * collider.fill / 2.0;
+        if collider.shape == ColliderShape::Aabb {
+            // Extents of the rotated box along the world axes
+            let (sin, cos) = transform.rotation.to_euler(EulerRot::ZYX).0.sin_cos();
+            half_extents = Vec2::new(
+                half_extents.x * cos.abs() + half_extents.y * sin.abs(),
+                half_extents.x * sin.abs() + half_extents.y * cos.abs(),
+            );
+        }
+        if collider.half_extents != half_extents {
+            collider.half_extents = half_extents;
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub fn detect_collisions(
+    colliders: Query<(Entity, &Transform, &Collider, Option<&Velocity>, Has<Orbit>)>,
+    mut events: EventWriter<CollisionEvent>,
+) {
+    for [a, b] in colliders.iter_combinations() {
+        let (a_entity, a_transform, a_collider, a_velocity, a_orbits) = a;
+        let (b_entity, b_transform, b_collider, b_velocity, b_orbits) = b;
+        let a_free = a_velocity.is_some() && !a_orbits;
+        let b_free = b_velocity.is_some() && !b_orbits;
+        if !a_free && !b_free {
+            continue;
+        }
+        let Some(contact) = contact(
+            a_transform.translation.truncate(),
+            a_collider,
+            b_transform.translation.truncate(),
+            b_collider,
+        ) else {
+            continue;
+        };
+        let relative_velocity = a_velocity.map(|v| v.0).unwrap_or_default()
+            - b_velocity.map(|v| v.0).unwrap_or_default();
+        let impact_speed = (-relative_velocity.dot(contact.normal)).max(0.0);
+        // Report the freely moving entity first, that is the one responses act on. When
+        // both move freely each of them gets its own event.
+        if a_free {
+            events.write(CollisionEvent {
+                entity: a_entity,
+                other: b_entity,
+                normal: contact.normal,
+                depth: contact.depth,
+                impact_speed,
+            });
+        }
+        if b_free {
+            events.write(CollisionEvent {
+                entity: b_entity,
+                other: a_entity,
+                normal: -contact.normal,
+                depth: contact.depth,
+                impact_speed,
+            });
+        }
+    }
+}
+
+// Pushes freely moving entities out of the bodies they ran into and changes their
+// velocity according to the body's `CollisionResponse`
+pub fn resolve_collisions(
+    mut events: EventReader<CollisionEvent>,
+    mut movers: Query<(&mut Transform, &mut Velocity), Without<Orbit>>,
+    bodies: Query<(&CollisionResponse, Option<&Velocity>)>,
+) {
+    for event in events.read() {
+        let Ok((response, body_velocity)) = bodies.get(event.other) else {
+            continue;
+        };
+        let body_velocity = body_velocity.map(|v| v.0).unwrap_or_default();
+        let Ok((mut transform, mut velocity)) = movers.get_mut(event.entity) else {
+            continue;
+        };
+        transform.translation += (event.normal * event.depth).extend(0.0);
+        let relative = velocity.0 - body_velocity;
+        let closing = relative.dot(event.normal);
+        let restitution = match *response {
+            CollisionResponse::Bounce { restitution } => restitution,
+            CollisionResponse::Land { safe_speed } if event.impact_speed > safe_speed => {
+                HARD_LANDING_RESTITUTION
+            }
+            CollisionResponse::Land { .. } | CollisionResponse::Crash => {
+                // Come to rest on the surface
+                velocity.0 = body_velocity;
+                continue;
+            }
+        };
+        if closing < 0.0 {
+            velocity.0 -= (1.0 + restitution) * closing * event.normal;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sized(collider: Collider, half_extents: Vec2) -> Collider {
+        Collider {
+            half_extents,
+            ..collider
+        }
+    }
+
+    #[test]
+    fn circles_touch_when_closer_than_their_radii() {
+        let circle = sized(Collider::circle(1.0), Vec2::splat(10.0));
+        let hit = contact(Vec2::new(15.0, 0.0), &circle, Vec2::ZERO, &circle).unwrap();
+        assert_eq!(hit.normal, Vec2::X);
+        assert_eq!(hit.depth, 5.0);
+        assert_eq!(
+            contact(Vec2::new(25.0, 0.0), &circle, Vec2::ZERO, &circle),
+            None
+        );
+    }
+
+    #[test]
+    fn circle_is_pushed_off_the_nearest_side_of_a_box() {
+        let circle = sized(Collider::circle(1.0), Vec2::splat(5.0));
+        let aabb = sized(Collider::aabb(1.0), Vec2::new(20.0, 10.0));
+        let hit = contact(Vec2::new(0.0, 13.0), &circle, Vec2::ZERO, &aabb).unwrap();
+        assert_eq!(hit.normal, Vec2::Y);
+        assert_eq!(hit.depth, 2.0);
+        // Swapping the two flips the normal
+        let swapped = contact(Vec2::ZERO, &aabb, Vec2::new(0.0, 13.0), &circle).unwrap();
+        assert_eq!(swapped.normal, Vec2::NEG_Y);
+        // Center inside the box
+        let inside = contact(Vec2::new(-18.0, 0.0), &circle, Vec2::ZERO, &aabb).unwrap();
+        assert_eq!(inside.normal, Vec2::NEG_X);
+        assert_eq!(inside.depth, 7.0);
+    }
+
+    #[test]
+    fn boxes_separate_along_the_shallowest_overlap() {
+        let aabb = sized(Collider::aabb(1.0), Vec2::splat(10.0));
+        let hit = contact(Vec2::new(-5.0, 18.0), &aabb, Vec2::ZERO, &aabb).unwrap();
+        assert_eq!(hit.normal, Vec2::Y);
+        assert_eq!(hit.depth, 2.0);
+        assert_eq!(
+            contact(Vec2::new(20.0, 0.0), &aabb, Vec2::ZERO, &aabb),
+            None
+        );
+    }
+}
@@ -0,0 +1,101 @@
+use crate::physics::Velocity;
+use bevy::prelude::*;
+
+// Gravitational constant of the game world. Masses are tuned against it, so it only
+// exists to keep the formula readable.
+pub const GRAVITATIONAL_CONSTANT: f32 = 1.0;
+
+// A body pulling everything that has a `Velocity` towards it
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct GravitySource {
+    pub mass: f32,
+    // Radius of the body's surface. Below it the pull fades out linearly towards the
+    // center, like inside a uniform sphere, so there is no singularity to fall into.
+    pub radius: f32,
+}
+
+impl GravitySource {
+    pub const SUN: GravitySource = GravitySource {
+        mass: 2.7e8,
+        radius: 1400.0,
+    };
+    pub const BASE: GravitySource = GravitySource {
+        mass: 5.8e7,
+        radius: 1200.0,
+    };
+    pub const MOON: GravitySource = GravitySource {
+        mass: 3.7e6,
+        radius: 430.0,
+    };
+}
+
+// Acceleration felt by a body at `position` towards a source at `source_position`
+pub fn gravity_acceleration(source_position: Vec2, source: &GravitySource, position: Vec2) -> Vec2 {
+    let offset = source_position - position;
+    let distance = offset.length();
+    if distance <= f32::EPSILON || source.radius <= 0.0 {
+        return Vec2::ZERO;
+    }
+    let strength = GRAVITATIONAL_CONSTANT * source.mass;
+    let magnitude = if distance >= source.radius {
+        strength / (distance * distance)
+    } else {
+        strength * distance / source.radius.powi(3)
+    };
+    offset / distance * magnitude
+}
+
+pub fn apply_gravity(
+    sources: Query<(&Transform, &GravitySource)>,
+    mut bodies: Query<(&Transform, &mut Velocity), Without<GravitySource>>,
+    time: Res<Time>,
+) {
+    let dt = time.delta_secs();
+    for (transform, mut velocity) in &mut bodies {
+        let position = transform.translation.truncate();
+        let acceleration: Vec2 = sources
+            .iter()
+            .map(|(source_transform, source)| {
+                gravity_acceleration(source_transform.translation.truncate(), source, position)
+            })
+            .sum();
+        velocity.0 += acceleration * dt;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: GravitySource = GravitySource {
+        mass: 1.0e6,
+        radius: 100.0,
+    };
+
+    #[test]
+    fn pull_falls_off_with_the_square_of_the_distance() {
+        let near = gravity_acceleration(Vec2::ZERO, &SOURCE, Vec2::new(200.0, 0.0));
+        let far = gravity_acceleration(Vec2::ZERO, &SOURCE, Vec2::new(400.0, 0.0));
+        assert!((near.length() - 25.0).abs() < 1e-4);
+        assert!((near.length() / far.length() - 4.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn pull_points_at_the_source() {
+        let source_position = Vec2::new(50.0, -20.0);
+        let position = Vec2::new(350.0, 380.0);
+        let acceleration = gravity_acceleration(source_position, &SOURCE, position);
+        let towards = (source_position - position).normalize();
+        assert!(acceleration.normalize().distance(towards) < 1e-5);
+    }
+
+    #[test]
+    fn pull_fades_out_inside_the_radius() {
+        let surface = gravity_acceleration(Vec2::ZERO, &SOURCE, Vec2::new(0.0, 100.0));
+        let halfway = gravity_acceleration(Vec2::ZERO, &SOURCE, Vec2::new(0.0, 50.0));
+        let center = gravity_acceleration(Vec2::ZERO, &SOURCE, Vec2::ZERO);
+        assert!((surface.length() - 100.0).abs() < 1e-4);
+        assert!((halfway.length() - 50.0).abs() < 1e-4);
+        assert_eq!(center, Vec2::ZERO);
+    }
+}
@@ -0,0 +1,60 @@
+// Shared motion for everything that moves through space: the player's ship, pirates,
+// raiders and projectiles
+use crate::pause::PauseState;
+use crate::planets::orbit::Orbit;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+pub mod collision;
+pub mod gravity;
+
+pub fn physics_plugin(app: &mut App) {
+    app.add_event::<collision::CollisionEvent>()
+        .configure_sets(
+            Update,
+            (
+                PhysicsSet::Forces,
+                PhysicsSet::Integrate,
+                PhysicsSet::Collide,
+            )
+                .chain(),
+        )
+        .add_systems(
+            Update,
+            (
+                gravity::apply_gravity.in_set(PhysicsSet::Forces),
+                apply_velocity.in_set(PhysicsSet::Integrate),
+                (
+                    collision::size_colliders,
+                    collision::detect_collisions,
+                    collision::resolve_collisions,
+                )
+                    .chain()
+                    .in_set(PhysicsSet::Collide),
+            )
+                .run_if(in_state(PauseState::Running)),
+        );
+}
+
+// Ordering of the motion systems: anything steering an entity runs before external
+// forces are applied, then velocities are integrated into transforms and overlapping
+// colliders are pushed apart
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PhysicsSet {
+    Forces,
+    Integrate,
+    Collide,
+}
+
+// Velocity in world units per second
+#[derive(
+    Component, Debug, Clone, Copy, Default, PartialEq, Deref, DerefMut, Serialize, Deserialize,
+)]
+pub struct Velocity(pub Vec2);
+
+// Orbiting bodies also carry a velocity, but their motion is driven by the orbit
+fn apply_velocity(mut query: Query<(&mut Transform, &Velocity), Without<Orbit>>, time: Res<Time>) {
+    for (mut transform, velocity) in &mut query {
+        transform.translation += (velocity.0 * time.delta_secs()).extend(0.0);
+    }
+}
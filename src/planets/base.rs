@@ -1,3 +1,5 @@
+use crate::planets::orbit::Orbit;
+use crate::planets::sun::SUN_POSITION;
 use bevy::prelude::*;
 
 #[derive(Component)]
@@ -22,3 +24,8 @@ pub fn spawn_base(
         ))
         .id()
 }
+
+// The Base orbits the Sun, starting at its periapsis at the origin
+pub fn base_orbit(sun: Entity) -> Orbit {
+    Orbit::through_periapsis(sun, -SUN_POSITION, 0.05, 1200.0)
+}
@@ -1,4 +1,5 @@
 // Re-export base and moon modules for easier import
 pub mod base;
 pub mod moon;
+pub mod orbit;
 pub mod sun;
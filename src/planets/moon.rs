@@ -1,8 +1,12 @@
+use crate::planets::orbit::Orbit;
 use bevy::prelude::*;
 
 #[derive(Component)]
 pub struct Moon;
 
+// Where the Moon starts, relative to the Base at the origin
+pub const MOON_START_OFFSET: Vec2 = Vec2::new(4600.0, 4400.0);
+
 pub fn spawn_moon(commands: &mut Commands, asset_server: &Res<AssetServer>) -> Entity {
     let texture_handle = asset_server.load("moon.png");
     commands
@@ -12,8 +16,13 @@ pub fn spawn_moon(commands: &mut Commands, asset_server: &Res<AssetServer>) -> E
                 ..default()
             },
             Transform::from_scale(Vec3::splat(2.5))
-                .with_translation(Vec3::new(4600.0, 4400.0, -1.0)),
+                .with_translation(MOON_START_OFFSET.extend(-1.0)),
             Moon,
         ))
         .id()
 }
+
+// The Moon orbits the Base, starting at its periapsis
+pub fn moon_orbit(base: Entity) -> Orbit {
+    Orbit::through_periapsis(base, MOON_START_OFFSET, 0.1, 600.0)
+}
@@ -0,0 +1,147 @@
+use crate::physics::Velocity;
+use bevy::prelude::*;
+use std::f32::consts::TAU;
+
+// Keplerian orbit of a body around its parent. The body's transform and velocity are
+// driven by the orbit every frame, so it is moved by `place_orbiting_bodies` rather
+// than by the physics integration.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Orbit {
+    pub parent: Entity,
+    pub semi_major_axis: f32,
+    pub eccentricity: f32,
+    // Time for a full revolution, in seconds
+    pub period: f32,
+    // Current mean anomaly in radians, 0.0 at periapsis
+    pub phase: f32,
+    // Direction of the periapsis from the parent, in radians
+    pub periapsis_angle: f32,
+}
+
+impl Orbit {
+    // Offset from the parent for the current phase
+    pub fn offset(&self) -> Vec2 {
+        orbit_offset(
+            self.semi_major_axis,
+            self.eccentricity,
+            self.phase,
+            self.periapsis_angle,
+        )
+    }
+
+    // Builds an orbit whose periapsis is exactly at `offset` from the parent, handy to
+    // keep the starting layout of the system
+    pub fn through_periapsis(parent: Entity, offset: Vec2, eccentricity: f32, period: f32) -> Self {
+        Self {
+            parent,
+            semi_major_axis: offset.length() / (1.0 - eccentricity),
+            eccentricity,
+            period,
+            phase: 0.0,
+            periapsis_angle: offset.to_angle(),
+        }
+    }
+}
+
+// Position relative to the focus of an orbit with the given elements, solving Kepler's
+// equation for the eccentric anomaly with a few Newton iterations
+pub fn orbit_offset(
+    semi_major_axis: f32,
+    eccentricity: f32,
+    mean_anomaly: f32,
+    periapsis_angle: f32,
+) -> Vec2 {
+    let mut eccentric_anomaly = mean_anomaly;
+    for _ in 0..6 {
+        let delta = (eccentric_anomaly - eccentricity * eccentric_anomaly.sin() - mean_anomaly)
+            / (1.0 - eccentricity * eccentric_anomaly.cos());
+        eccentric_anomaly -= delta;
+    }
+    let x = semi_major_axis * (eccentric_anomaly.cos() - eccentricity);
+    let y = semi_major_axis * (1.0 - eccentricity * eccentricity).sqrt() * eccentric_anomaly.sin();
+    Vec2::from_angle(periapsis_angle).rotate(Vec2::new(x, y))
+}
+
+pub fn advance_orbits(mut orbits: Query<&mut Orbit>, time: Res<Time>) {
+    for mut orbit in &mut orbits {
+        if orbit.period > 0.0 {
+            orbit.phase = (orbit.phase + TAU / orbit.period * time.delta_secs()).rem_euclid(TAU);
+        }
+    }
+}
+
+// Moves every orbiting body to its place around its parent. Parents may orbit
+// themselves (the Moon around the Base around the Sun), so positions are resolved up
+// the chain before anything is written.
+pub fn place_orbiting_bodies(
+    orbits: Query<(Entity, &Orbit)>,
+    mut bodies: Query<(&mut Transform, Option<&mut Velocity>)>,
+    time: Res<Time>,
+) {
+    let positions: Vec<(Entity, Vec2)> = orbits
+        .iter()
+        .filter_map(|(entity, _)| {
+            resolve_position(entity, &orbits, &bodies).map(|position| (entity, position))
+        })
+        .collect();
+    let dt = time.delta_secs();
+    for (entity, position) in positions {
+        if let Ok((mut transform, velocity)) = bodies.get_mut(entity) {
+            if let Some(mut velocity) = velocity
+                && dt > 0.0
+            {
+                velocity.0 = (position - transform.translation.truncate()) / dt;
+            }
+            transform.translation = position.extend(transform.translation.z);
+        }
+    }
+}
+
+fn resolve_position(
+    entity: Entity,
+    orbits: &Query<(Entity, &Orbit)>,
+    bodies: &Query<(&mut Transform, Option<&mut Velocity>)>,
+) -> Option<Vec2> {
+    match orbits.get(entity) {
+        Ok((_, orbit)) => {
+            resolve_position(orbit.parent, orbits, bodies).map(|parent| parent + orbit.offset())
+        }
+        Err(_) => bodies
+            .get(entity)
+            .ok()
+            .map(|(transform, _)| transform.translation.truncate()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: Vec2, b: Vec2) {
+        assert!(a.distance(b) < 1e-2, "{a} is not {b}");
+    }
+
+    #[test]
+    fn periapsis_and_apoapsis_are_on_the_major_axis() {
+        // a = 1000, e = 0.5: 500 from the focus at periapsis, 1500 at apoapsis
+        assert_close(orbit_offset(1000.0, 0.5, 0.0, 0.0), Vec2::new(500.0, 0.0));
+        assert_close(
+            orbit_offset(1000.0, 0.5, std::f32::consts::PI, 0.0),
+            Vec2::new(-1500.0, 0.0),
+        );
+    }
+
+    #[test]
+    fn circular_orbit_moves_at_a_steady_rate() {
+        let quarter = orbit_offset(800.0, 0.0, TAU / 4.0, 0.0);
+        assert_close(quarter, Vec2::new(0.0, 800.0));
+    }
+
+    #[test]
+    fn through_periapsis_starts_at_the_given_offset() {
+        let offset = Vec2::new(-300.0, 400.0);
+        let orbit = Orbit::through_periapsis(Entity::PLACEHOLDER, offset, 0.2, 60.0);
+        assert_close(orbit.offset(), offset);
+        assert!((orbit.semi_major_axis - 625.0).abs() < 1e-3);
+    }
+}
@@ -1,23 +1,42 @@
+use crate::DisplayQuality;
 use bevy::prelude::*;
 
 #[derive(Component)]
 pub struct Sun;
 
-pub fn spawn_sun(commands: &mut Commands, asset_server: &Res<AssetServer>) {
+// The Sun sits still, everything else orbits around it
+pub const SUN_POSITION: Vec2 = Vec2::new(8000.0, 8000.0);
+
+pub fn spawn_sun(commands: &mut Commands, asset_server: &Res<AssetServer>) -> Entity {
     let texture_handle = asset_server.load("sun.png");
-    commands.spawn((
-        Sprite {
-            image: texture_handle,
-            ..default()
-        },
-        Transform::from_scale(Vec3::splat(8.0)).with_translation(Vec3::new(8000.0, 8000.0, -1.0)),
-        Sun,
-    ));
+    commands
+        .spawn((
+            Sprite {
+                image: texture_handle,
+                ..default()
+            },
+            Transform::from_scale(Vec3::splat(8.0)).with_translation(SUN_POSITION.extend(-1.0)),
+            Sun,
+        ))
+        .id()
 }
 
-// System to rotate the sun slowly
-pub fn rotate_sun(mut query: Query<&mut Transform, With<Sun>>) {
+// Radians per second
+const SUN_SPIN_SPEED: f32 = 0.6;
+
+// System to rotate the sun slowly, in steps as often as the display quality allows
+pub fn rotate_sun(
+    mut query: Query<&mut Transform, With<Sun>>,
+    time: Res<Time>,
+    display_quality: Res<DisplayQuality>,
+    mut pending: Local<f32>,
+) {
+    *pending += time.delta_secs();
+    if *pending < display_quality.profile().animation_step {
+        return;
+    }
     for mut transform in &mut query {
-        transform.rotate_z(0.01); // Adjust speed as desired
+        transform.rotate_z(SUN_SPIN_SPEED * *pending);
     }
+    *pending = 0.0;
 }
@@ -0,0 +1,83 @@
+// What each `DisplayQuality` level means for rendering. Systems read the profile of the
+// current level every time they use it, so a change in the settings applies right away.
+use crate::DisplayQuality;
+use bevy::prelude::*;
+
+pub fn quality_plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        apply_msaa.run_if(resource_changed::<DisplayQuality>),
+    );
+}
+
+// The concrete knobs behind a quality level
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct QualityProfile {
+    pub star_layers: usize,
+    pub stars_per_layer: usize,
+    // Fraction of the full particle count spawned by effects
+    pub particle_scale: f32,
+    // Seconds between two steps of sprite animations, 0.0 steps every frame
+    pub animation_step: f32,
+    // Seconds between two refreshes of the HUD, 0.0 refreshes every frame
+    pub ui_refresh: f32,
+    pub msaa: Msaa,
+}
+
+impl DisplayQuality {
+    pub fn profile(self) -> QualityProfile {
+        match self {
+            DisplayQuality::Low => QualityProfile {
+                star_layers: 2,
+                stars_per_layer: 60,
+                particle_scale: 0.4,
+                animation_step: 1.0 / 10.0,
+                ui_refresh: 0.25,
+                msaa: Msaa::Off,
+            },
+            DisplayQuality::Medium => QualityProfile {
+                star_layers: 3,
+                stars_per_layer: 100,
+                particle_scale: 1.0,
+                animation_step: 1.0 / 30.0,
+                ui_refresh: 0.1,
+                msaa: Msaa::Sample4,
+            },
+            // 4 samples is the most every graphics backend supports
+            DisplayQuality::High => QualityProfile {
+                star_layers: 4,
+                stars_per_layer: 160,
+                particle_scale: 1.5,
+                animation_step: 0.0,
+                ui_refresh: 0.0,
+                msaa: Msaa::Sample4,
+            },
+        }
+    }
+}
+
+fn apply_msaa(quality: Res<DisplayQuality>, mut cameras: Query<&mut Msaa, With<Camera>>) {
+    let msaa = quality.profile().msaa;
+    for mut camera_msaa in &mut cameras {
+        if *camera_msaa != msaa {
+            *camera_msaa = msaa;
+        }
+    }
+}
+
+// Run condition letting a HUD system run at the cadence of the current quality level.
+// The first call always runs, so that the HUD shows up at once.
+pub fn ui_refresh_due(
+    quality: Res<DisplayQuality>,
+    time: Res<Time>,
+    mut since_refresh: Local<Option<f32>>,
+) -> bool {
+    let elapsed = since_refresh.map_or(f32::INFINITY, |since| since + time.delta_secs());
+    if elapsed >= quality.profile().ui_refresh {
+        *since_refresh = Some(0.0);
+        true
+    } else {
+        *since_refresh = Some(elapsed);
+        false
+    }
+}
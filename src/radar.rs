@@ -0,0 +1,224 @@
+// A radar in the corner of the screen plotting everything with a `RadarSignature`
+// around the player's ship, north up. The range is switched with the Radar Range action.
+use crate::controls::{Action, ActionState};
+use crate::pause::PauseState;
+use crate::quality::ui_refresh_due;
+use crate::ship::control::PlayerControlled;
+use crate::space::OnSpaceScreen;
+use crate::{GameState, TEXT_COLOR};
+use bevy::ecs::spawn::SpawnIter;
+use bevy::prelude::*;
+
+const RADAR_SIZE: f32 = 200.0;
+// Distances shown from the center to the edge. The first one is used until the pilot
+// switches, whose choice then holds until the game is closed.
+const RADAR_RANGES: [f32; 3] = [3000.0, 8000.0, 20000.0];
+// Rings at these fractions of the range, the edge of the radar being the last one
+const RANGE_RINGS: [f32; 2] = [1.0 / 3.0, 2.0 / 3.0];
+const RADAR_BACKGROUND: Color = Color::srgba(0.0, 0.05, 0.0, 0.6);
+const RADAR_LINES: Color = Color::srgba(0.3, 0.9, 0.3, 0.5);
+
+pub fn radar_plugin(app: &mut App) {
+    app.init_resource::<RadarRange>()
+        .add_systems(OnEnter(GameState::Space), spawn_radar)
+        .add_systems(
+            Update,
+            (
+                cycle_radar_range,
+                update_range_label.run_if(resource_changed::<RadarRange>),
+                add_blips,
+                update_blips.after(add_blips).run_if(ui_refresh_due),
+            )
+                .run_if(in_state(PauseState::Running)),
+        );
+}
+
+// What something looks like on the radar
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RadarSignature {
+    Sun,
+    // Anything the ship can dock at
+    Station,
+    Hostile,
+}
+
+impl RadarSignature {
+    // Size of the blip in pixels, its color and whether it is round rather than square
+    pub fn icon(self) -> (f32, Color, bool) {
+        match self {
+            RadarSignature::Sun => (14.0, Color::srgb(1.0, 0.8, 0.2), true),
+            RadarSignature::Station => (10.0, Color::srgb(0.2, 0.8, 1.0), false),
+            RadarSignature::Hostile => (6.0, Color::srgb(1.0, 0.2, 0.2), false),
+        }
+    }
+}
+
+// Index into `RADAR_RANGES` of the range shown
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RadarRange(usize);
+
+impl RadarRange {
+    fn distance(self) -> f32 {
+        RADAR_RANGES[self.0]
+    }
+
+    fn label(self) -> String {
+        format!("{:.0}", self.distance())
+    }
+}
+
+// Tag component for the radar's disc, parent of the blips
+#[derive(Component)]
+struct RadarScreen;
+
+#[derive(Component)]
+struct RangeLabel;
+
+// The blip of an entity with a `RadarSignature`
+#[derive(Component)]
+struct RadarBlip(Entity);
+
+fn spawn_radar(mut commands: Commands, range: Res<RadarRange>) {
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            right: Val::Px(24.0),
+            bottom: Val::Px(24.0),
+            width: Val::Px(RADAR_SIZE),
+            height: Val::Px(RADAR_SIZE),
+            border: UiRect::all(Val::Px(1.0)),
+            overflow: Overflow::clip(),
+            ..default()
+        },
+        BackgroundColor(RADAR_BACKGROUND),
+        BorderColor(RADAR_LINES),
+        BorderRadius::MAX,
+        RadarScreen,
+        OnSpaceScreen,
+        Children::spawn((
+            SpawnIter(RANGE_RINGS.into_iter().map(|fraction| {
+                let size = RADAR_SIZE * fraction;
+                (
+                    Node {
+                        position_type: PositionType::Absolute,
+                        left: Val::Px((RADAR_SIZE - size) / 2.0),
+                        top: Val::Px((RADAR_SIZE - size) / 2.0),
+                        width: Val::Px(size),
+                        height: Val::Px(size),
+                        border: UiRect::all(Val::Px(1.0)),
+                        ..default()
+                    },
+                    BorderColor(RADAR_LINES),
+                    BorderRadius::MAX,
+                )
+            })),
+            // The player's ship, always in the middle
+            Spawn((
+                Node {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(RADAR_SIZE / 2.0 - 3.0),
+                    top: Val::Px(RADAR_SIZE / 2.0 - 3.0),
+                    width: Val::Px(6.0),
+                    height: Val::Px(6.0),
+                    ..default()
+                },
+                BackgroundColor(Color::WHITE),
+                BorderRadius::MAX,
+            )),
+            Spawn((
+                Text::new(range.label()),
+                TextFont {
+                    font_size: 12.0,
+                    ..default()
+                },
+                TextColor(TEXT_COLOR),
+                Node {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(14.0),
+                    width: Val::Percent(100.0),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                TextLayout::new_with_justify(JustifyText::Center),
+                RangeLabel,
+            )),
+        )),
+    ));
+}
+
+fn cycle_radar_range(actions: Res<ActionState>, mut range: ResMut<RadarRange>) {
+    if actions.just_pressed(Action::RadarRange) {
+        range.0 = (range.0 + 1) % RADAR_RANGES.len();
+    }
+}
+
+fn update_range_label(range: Res<RadarRange>, mut labels: Query<&mut Text, With<RangeLabel>>) {
+    for mut text in &mut labels {
+        text.0 = range.label();
+    }
+}
+
+// Gives a blip to everything that shows up on the radar. The radar is spawned along
+// with the rest of the space entities, so it never misses one.
+fn add_blips(
+    mut commands: Commands,
+    radar: Query<Entity, With<RadarScreen>>,
+    signatures: Query<(Entity, &RadarSignature), Added<RadarSignature>>,
+) {
+    let Ok(screen) = radar.single() else {
+        return;
+    };
+    for (target, signature) in &signatures {
+        let (size, color, round) = signature.icon();
+        commands.entity(screen).with_child((
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Px(size),
+                height: Val::Px(size),
+                ..default()
+            },
+            BackgroundColor(color),
+            if round {
+                BorderRadius::MAX
+            } else {
+                BorderRadius::ZERO
+            },
+            // Placed by `update_blips`
+            Visibility::Hidden,
+            RadarBlip(target),
+        ));
+    }
+}
+
+fn update_blips(
+    mut commands: Commands,
+    range: Res<RadarRange>,
+    ships: Query<&Transform, With<PlayerControlled>>,
+    targets: Query<(&Transform, &InheritedVisibility), With<RadarSignature>>,
+    mut blips: Query<(Entity, &RadarBlip, &mut Node, &mut Visibility)>,
+) {
+    let Ok(ship) = ships.single() else {
+        return;
+    };
+    let center = ship.translation.truncate();
+    let scale = RADAR_SIZE / 2.0 / range.distance();
+    for (blip, RadarBlip(target), mut node, mut visibility) in &mut blips {
+        let Ok((transform, target_visibility)) = targets.get(*target) else {
+            // The target is gone
+            commands.entity(blip).despawn();
+            continue;
+        };
+        // Screen y grows downwards
+        let offset = (transform.translation.truncate() - center) * scale * Vec2::new(1.0, -1.0);
+        let Val::Px(size) = node.width else {
+            continue;
+        };
+        if offset.length() > RADAR_SIZE / 2.0 || !target_visibility.get() {
+            visibility.set_if_neq(Visibility::Hidden);
+            continue;
+        }
+        visibility.set_if_neq(Visibility::Inherited);
+        node.left = Val::Px(RADAR_SIZE / 2.0 + offset.x - size / 2.0);
+        node.top = Val::Px(RADAR_SIZE / 2.0 + offset.y - size / 2.0);
+    }
+}
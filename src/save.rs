@@ -0,0 +1,247 @@
+// Games saved to slots in the configuration directory. Slot 0 is written automatically
+// each time the ship docks, the others only when the pilot asks from a station or the
+// pause overlay. A save holds everything `reset_game` would otherwise start afresh.
+use crate::GameState;
+use crate::config;
+use crate::defense::{GameMode, Waves};
+use crate::docked::DockedStation;
+use crate::economy::Wallet;
+use crate::game_over::RunStats;
+use crate::session::{GameSession, save_session};
+use crate::ship::movement::{FlightModel, RetroThrust};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const AUTOSAVE_SLOT: usize = 0;
+// Slots the pilot saves to, after the autosave one
+pub const MANUAL_SLOTS: [usize; 3] = [1, 2, 3];
+const SLOT_COUNT: usize = 4;
+// Bumped whenever `SaveGame` changes, with a conversion added to `parse_save`
+const SAVE_VERSION: u32 = 1;
+
+pub fn save_plugin(app: &mut App) {
+    app.add_event::<SaveRequest>()
+        .insert_resource(SaveSlots::scan())
+        .add_systems(OnEnter(GameState::Docked), autosave)
+        // Saving from the pause overlay first takes a fresh snapshot of the world
+        .add_systems(
+            Update,
+            (save_session.run_if(in_state(GameState::Space)), write_saves)
+                .chain()
+                .run_if(on_event::<SaveRequest>),
+        );
+}
+
+// Asks for the current game to be written to a slot
+#[derive(Event, Debug, Clone, Copy)]
+pub struct SaveRequest(pub usize);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaveGame {
+    version: u32,
+    // Seconds since the Unix epoch, to tell which save is the latest
+    saved_at: u64,
+    game_mode: GameMode,
+    flight_model: FlightModel,
+    retro_thrust: RetroThrust,
+    session: GameSession,
+    wallet: Wallet,
+    stats: RunStats,
+    // Progress through the waves in Z Defense
+    waves: Option<Waves>,
+}
+
+// Only the version is read first, to know how to read the rest
+#[derive(Deserialize)]
+struct SaveHeader {
+    version: u32,
+}
+
+// What the menus show of each slot, `None` for empty slots
+#[derive(Resource, Debug, Default)]
+pub struct SaveSlots([Option<SaveSummary>; SLOT_COUNT]);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SaveSummary {
+    pub saved_at: u64,
+    pub game_mode: GameMode,
+    pub credits: u32,
+    pub time_played: f32,
+}
+
+impl SaveSlots {
+    fn scan() -> Self {
+        Self(std::array::from_fn(|slot| {
+            read_slot(slot).map(|save| save.summary())
+        }))
+    }
+
+    pub fn get(&self, slot: usize) -> Option<&SaveSummary> {
+        self.0.get(slot).and_then(Option::as_ref)
+    }
+
+    // The slot saved to last, if any
+    pub fn latest(&self) -> Option<usize> {
+        (0..SLOT_COUNT)
+            .filter_map(|slot| self.get(slot).map(|summary| (slot, summary.saved_at)))
+            .max_by_key(|(_, saved_at)| *saved_at)
+            .map(|(slot, _)| slot)
+    }
+}
+
+impl SaveSummary {
+    // One line describing the save, e.g. "Z Defense, 1200 cr, 12:34 played"
+    pub fn describe(&self) -> String {
+        let seconds = self.time_played as u32;
+        format!(
+            "{}, {} cr, {}:{:02} played",
+            self.game_mode.label(),
+            self.credits,
+            seconds / 60,
+            seconds % 60
+        )
+    }
+}
+
+impl SaveGame {
+    fn summary(&self) -> SaveSummary {
+        SaveSummary {
+            saved_at: self.saved_at,
+            game_mode: self.game_mode,
+            credits: self.wallet.credits,
+            time_played: self.stats.time_survived,
+        }
+    }
+}
+
+pub fn slot_label(slot: usize) -> String {
+    if slot == AUTOSAVE_SLOT {
+        "Autosave".to_string()
+    } else {
+        format!("Slot {slot}")
+    }
+}
+
+fn slot_file(slot: usize) -> String {
+    format!("saves/slot_{slot}.ron")
+}
+
+pub fn read_slot(slot: usize) -> Option<SaveGame> {
+    config::load_with(&slot_file(slot), parse_save)
+}
+
+// Reads a save of any known version. Older versions are converted to the current
+// layout here, through the structs they were written from.
+fn parse_save(contents: &str) -> Result<SaveGame, String> {
+    let header: SaveHeader = ron::from_str(contents).map_err(|error| error.to_string())?;
+    match header.version {
+        SAVE_VERSION => ron::from_str(contents).map_err(|error| error.to_string()),
+        version if version > SAVE_VERSION => Err(format!(
+            "save version {version} is newer than this game ({SAVE_VERSION})"
+        )),
+        version => Err(format!("no conversion from save version {version}")),
+    }
+}
+
+// Replaces the current game with a saved one. Like after docking, `setup_space` then
+// rebuilds the world from the session.
+pub fn load_game(commands: &mut Commands, save: SaveGame) {
+    commands.insert_resource(save.game_mode);
+    commands.insert_resource(save.flight_model);
+    commands.insert_resource(save.retro_thrust);
+    commands.insert_resource(save.session);
+    commands.insert_resource(save.wallet);
+    commands.insert_resource(save.stats);
+    match save.waves {
+        Some(waves) => commands.insert_resource(waves),
+        None => commands.remove_resource::<Waves>(),
+    }
+    // The ship is placed where it was saved, not next to the last station
+    commands.remove_resource::<DockedStation>();
+}
+
+fn autosave(mut requests: EventWriter<SaveRequest>) {
+    requests.write(SaveRequest(AUTOSAVE_SLOT));
+}
+
+#[allow(clippy::too_many_arguments)]
+fn write_saves(
+    mut requests: EventReader<SaveRequest>,
+    mut slots: ResMut<SaveSlots>,
+    game_mode: Res<GameMode>,
+    flight_model: Res<FlightModel>,
+    retro_thrust: Res<RetroThrust>,
+    session: Res<GameSession>,
+    wallet: Res<Wallet>,
+    stats: Res<RunStats>,
+    waves: Option<Res<Waves>>,
+) {
+    let saved_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    let save = SaveGame {
+        version: SAVE_VERSION,
+        saved_at,
+        game_mode: *game_mode,
+        flight_model: *flight_model,
+        retro_thrust: *retro_thrust,
+        session: session.clone(),
+        wallet: *wallet,
+        stats: stats.clone(),
+        waves: waves.map(|waves| waves.clone()),
+    };
+    for SaveRequest(slot) in requests.read() {
+        if *slot < SLOT_COUNT && config::save(&slot_file(*slot), &save) {
+            slots.0[*slot] = Some(save.summary());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn save_game() -> SaveGame {
+        SaveGame {
+            version: SAVE_VERSION,
+            saved_at: 1_700_000_000,
+            game_mode: GameMode::default(),
+            flight_model: FlightModel::Newtonian,
+            retro_thrust: RetroThrust(false),
+            session: GameSession::default(),
+            wallet: Wallet {
+                credits: 1234,
+                earned: 56,
+            },
+            stats: RunStats::default(),
+            waves: None,
+        }
+    }
+
+    #[test]
+    fn current_version_is_read_back() {
+        let contents = ron::to_string(&save_game()).unwrap();
+        let save = parse_save(&contents).unwrap();
+        assert_eq!(save.saved_at, 1_700_000_000);
+        assert_eq!(save.flight_model, FlightModel::Newtonian);
+        assert_eq!(save.retro_thrust, RetroThrust(false));
+        assert_eq!(save.wallet.credits, 1234);
+    }
+
+    #[test]
+    fn newer_versions_are_refused() {
+        let save = SaveGame {
+            version: SAVE_VERSION + 1,
+            ..save_game()
+        };
+        let error = parse_save(&ron::to_string(&save).unwrap()).unwrap_err();
+        assert!(error.contains("newer"), "{error}");
+    }
+
+    #[test]
+    fn versions_without_a_conversion_are_refused() {
+        let error = parse_save("(version: 0)").unwrap_err();
+        assert!(error.contains("no conversion"), "{error}");
+    }
+}
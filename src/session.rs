@@ -0,0 +1,96 @@
+use crate::ai::AiPilot;
+use crate::combat::Health;
+use crate::defense::Waves;
+use crate::economy::Wallet;
+use crate::game_over::RunStats;
+use crate::physics::Velocity;
+use crate::planets::base::Base;
+use crate::planets::moon::Moon;
+use crate::planets::orbit::Orbit;
+use crate::planets::sun::Sun;
+use crate::ship::control::PlayerControlled;
+use crate::ship::spaceship::Spaceship;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+// The state of the space world that survives leaving the space screen. It is filled
+// when exiting `GameState::Space` and read back by `setup_space`, so a trip to a
+// station does not reset the ship or the planets. Starting a new game replaces it
+// with an empty session.
+#[derive(Resource, Default, Debug, Clone, Serialize, Deserialize)]
+pub struct GameSession {
+    // Seed the world of this game is generated from
+    pub seed: u64,
+    pub ship: Option<ShipSnapshot>,
+    pub sun: Option<BodySnapshot>,
+    pub base: Option<BodySnapshot>,
+    pub moon: Option<BodySnapshot>,
+    // Pirates still flying, `None` until they have been launched
+    pub pirates: Option<Vec<ShipSnapshot>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShipSnapshot {
+    pub stats: Spaceship,
+    pub transform: Transform,
+    pub velocity: Velocity,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct BodySnapshot {
+    pub transform: Transform,
+    // Where the body was along its orbit, if it has one
+    pub orbit_phase: Option<f32>,
+    // Damage taken, for bodies that can be attacked
+    pub health: Option<Health>,
+}
+
+pub fn session_plugin(app: &mut App) {
+    app.init_resource::<GameSession>();
+}
+
+// Throws away the current game, so that the next visit to space starts a new one
+pub fn reset_game(commands: &mut Commands) {
+    commands.insert_resource(GameSession {
+        seed: rand::random(),
+        ..default()
+    });
+    commands.insert_resource(Wallet::default());
+    commands.insert_resource(RunStats::default());
+    commands.remove_resource::<Waves>();
+}
+
+// Snapshot the ship and the celestial bodies before the space entities are despawned
+#[allow(clippy::type_complexity)]
+pub fn save_session(
+    mut session: ResMut<GameSession>,
+    ship_query: Query<(&Spaceship, &Transform, &Velocity), With<PlayerControlled>>,
+    pirate_query: Query<(&Spaceship, &Transform, &Velocity), With<AiPilot>>,
+    sun_query: Query<(&Transform, Option<&Orbit>, Option<&Health>), With<Sun>>,
+    base_query: Query<(&Transform, Option<&Orbit>, Option<&Health>), With<Base>>,
+    moon_query: Query<(&Transform, Option<&Orbit>, Option<&Health>), With<Moon>>,
+) {
+    session.ship = ship_query.single().ok().map(ship_snapshot);
+    session.sun = sun_query.single().ok().map(body_snapshot);
+    session.base = base_query.single().ok().map(body_snapshot);
+    session.moon = moon_query.single().ok().map(body_snapshot);
+    session.pirates = Some(pirate_query.iter().map(ship_snapshot).collect());
+}
+
+fn ship_snapshot((ship, transform, velocity): (&Spaceship, &Transform, &Velocity)) -> ShipSnapshot {
+    ShipSnapshot {
+        stats: ship.clone(),
+        transform: *transform,
+        velocity: *velocity,
+    }
+}
+
+fn body_snapshot(
+    (transform, orbit, health): (&Transform, Option<&Orbit>, Option<&Health>),
+) -> BodySnapshot {
+    BodySnapshot {
+        transform: *transform,
+        orbit_phase: orbit.map(|orbit| orbit.phase),
+        health: health.copied(),
+    }
+}
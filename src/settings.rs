@@ -0,0 +1,104 @@
+// The player's settings, kept in the configuration directory between runs. They are
+// loaded before the menu is shown and written back whenever one of them changes.
+use crate::config;
+use crate::controls::Bindings;
+use crate::{DisplayQuality, MusicVolume, SfxVolume, Volume};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+const SETTINGS_FILE: &str = "settings.ron";
+// Bumped when the layout of `Settings` changes in a way old files can't be read as
+const SETTINGS_VERSION: u32 = 1;
+
+pub fn settings_plugin(app: &mut App) {
+    let settings = load_settings();
+    app.insert_resource(settings.display_quality)
+        .insert_resource(settings.volume)
+        .insert_resource(settings.music_volume)
+        .insert_resource(settings.sfx_volume)
+        .insert_resource(settings.bindings)
+        .add_systems(
+            Update,
+            save_settings.run_if(
+                resource_changed::<DisplayQuality>
+                    .or(resource_changed::<Volume>)
+                    .or(resource_changed::<MusicVolume>)
+                    .or(resource_changed::<SfxVolume>)
+                    .or(resource_changed::<Bindings>),
+            ),
+        );
+}
+
+// What is written to the settings file. Missing fields take their default value, so
+// that a file from an older version of the same layout still loads.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+struct Settings {
+    version: u32,
+    display_quality: DisplayQuality,
+    volume: Volume,
+    music_volume: MusicVolume,
+    sfx_volume: SfxVolume,
+    bindings: Bindings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            version: SETTINGS_VERSION,
+            display_quality: DisplayQuality::Medium,
+            volume: Volume(7),
+            music_volume: MusicVolume(9),
+            sfx_volume: SfxVolume(9),
+            bindings: Bindings::default(),
+        }
+    }
+}
+
+// Falls back to the defaults when the file is missing, corrupt, or written by a newer
+// version of the game
+fn load_settings() -> Settings {
+    let Some(settings) = config::load::<Settings>(SETTINGS_FILE) else {
+        return Settings::default();
+    };
+    if settings.version > SETTINGS_VERSION {
+        warn!(
+            "{SETTINGS_FILE} has version {}, newer than {SETTINGS_VERSION}, using default settings",
+            settings.version
+        );
+        return Settings::default();
+    }
+    let mut settings = Settings {
+        version: SETTINGS_VERSION,
+        ..settings
+    };
+    settings.bindings.fill_missing();
+    settings
+}
+
+// The first run only records the settings as loaded, so that starting the game
+// doesn't rewrite the file
+fn save_settings(
+    display_quality: Res<DisplayQuality>,
+    volume: Res<Volume>,
+    music_volume: Res<MusicVolume>,
+    sfx_volume: Res<SfxVolume>,
+    bindings: Res<Bindings>,
+    mut saved: Local<Option<Settings>>,
+) {
+    let settings = Settings {
+        version: SETTINGS_VERSION,
+        display_quality: *display_quality,
+        volume: *volume,
+        music_volume: *music_volume,
+        sfx_volume: *sfx_volume,
+        bindings: bindings.clone(),
+    };
+    if saved.as_ref() == Some(&settings) {
+        return;
+    }
+    if saved.is_some() {
+        config::save(SETTINGS_FILE, &settings);
+    }
+    *saved = Some(settings);
+}
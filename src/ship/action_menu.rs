@@ -1,4 +1,12 @@
 // Use the same UI idioms as ui.rs: Node, Button, Text, TextFont, TextColor, etc.
+use crate::controls::{Action, ActionState};
+use crate::defense::Waves;
+use crate::docked::DockedStation;
+use crate::economy::PriceTable;
+use crate::physics::Velocity;
+use crate::physics::collision::Collider;
+use crate::ship::control::PlayerControlled;
+use crate::space::OnSpaceScreen;
 use bevy::{color::palettes::basic::*, prelude::*};
 
 const NORMAL_BUTTON: Color = Color::srgb(0.15, 0.15, 0.15);
@@ -13,12 +21,17 @@ pub struct ActionMenuTarget {
 #[derive(Component)]
 pub struct ActionMenuRoot;
 
+// The "Dock" button remembers which target it docks at
+#[derive(Component)]
+pub struct DockButton(pub Entity);
+
 pub fn show_action_menu_system(
-    ship_query: Query<&Transform, With<crate::ship::spaceship::Spaceship>>,
-    target_query: Query<(&Transform, &ActionMenuTarget)>,
+    ship_query: Query<&Transform, With<PlayerControlled>>,
+    target_query: Query<(Entity, &Transform, &ActionMenuTarget, Option<&Collider>)>,
     mut commands: Commands,
     menu_query: Query<Entity, With<ActionMenuRoot>>,
     asset_server: Res<AssetServer>,
+    waves: Option<Res<Waves>>,
 ) {
     let ship_transform = match ship_query.single() {
         Ok(t) => t,
@@ -26,14 +39,18 @@ pub fn show_action_menu_system(
     };
     let ship_pos = ship_transform.translation.truncate();
     let mut show_menu = None;
-    for (target_transform, target) in &target_query {
+    // Stations turn ships away while a wave is attacking
+    let docking_open = waves.is_none_or(|waves| waves.docking_open());
+    for (target_entity, target_transform, target, collider) in &target_query {
         let target_pos = target_transform.translation.truncate();
-        if ship_pos.distance(target_pos) < 120.0 {
-            show_menu = Some(target.label.clone());
+        // Measured from the target's surface for targets with a body
+        let radius = collider.map(Collider::radius).unwrap_or_default();
+        if docking_open && ship_pos.distance(target_pos) - radius < 120.0 {
+            show_menu = Some((target_entity, target.label.clone()));
             break;
         }
     }
-    if let Some(label) = show_menu {
+    if let Some((target_entity, label)) = show_menu {
         // Show menu if not already present
         if menu_query.single().is_err() {
             commands
@@ -50,6 +67,7 @@ pub fn show_action_menu_system(
                     },
                     BackgroundColor(Color::WHITE.with_alpha(0.95)),
                     ActionMenuRoot,
+                    OnSpaceScreen,
                 ))
                 .with_children(|parent| {
                     parent.spawn((
@@ -59,7 +77,7 @@ pub fn show_action_menu_system(
                             font_size: 28.0,
                             ..default()
                         },
-                        TextColor(Color::BLACK.into()),
+                        TextColor(Color::BLACK),
                     ));
                     parent
                         .spawn((Node {
@@ -85,6 +103,7 @@ pub fn show_action_menu_system(
                                     BorderColor(Color::BLACK),
                                     BackgroundColor(NORMAL_BUTTON),
                                     BorderRadius::MAX,
+                                    DockButton(target_entity),
                                 ))
                                 .with_children(|button| {
                                     button.spawn((
@@ -109,6 +128,7 @@ pub fn show_action_menu_system(
     }
 }
 
+#[allow(clippy::type_complexity)]
 pub fn action_menu_button_system(
     mut interaction_query: Query<
         (
@@ -116,20 +136,24 @@ pub fn action_menu_button_system(
             &mut BackgroundColor,
             &mut BorderColor,
             &Children,
+            &DockButton,
         ),
         (Changed<Interaction>, With<Button>),
     >,
     mut text_query: Query<&mut Text>,
+    target_query: Query<DockTarget>,
+    mut commands: Commands,
     mut next_state: ResMut<bevy::prelude::NextState<crate::GameState>>,
 ) {
-    for (interaction, mut color, mut border_color, children) in &mut interaction_query {
+    for (interaction, mut color, mut border_color, children, dock_button) in &mut interaction_query
+    {
         let mut text = text_query.get_mut(children[0]).unwrap();
         match *interaction {
             Interaction::Pressed => {
                 **text = "Dock".to_string();
                 *color = PRESSED_BUTTON.into();
                 border_color.0 = RED.into();
-                next_state.set(crate::GameState::Docked); // Switch to docked scene
+                dock_at(dock_button.0, &target_query, &mut commands, &mut next_state);
             }
             Interaction::Hovered => {
                 **text = "Dock".to_string();
@@ -144,3 +168,47 @@ pub fn action_menu_button_system(
         }
     }
 }
+
+// The Interact action presses the "Dock" button while the menu is shown
+pub fn interact_to_dock(
+    actions: Res<ActionState>,
+    dock_buttons: Query<&DockButton>,
+    target_query: Query<DockTarget>,
+    mut commands: Commands,
+    mut next_state: ResMut<NextState<crate::GameState>>,
+) {
+    if !actions.just_pressed(Action::Interact) {
+        return;
+    }
+    if let Ok(dock_button) = dock_buttons.single() {
+        dock_at(dock_button.0, &target_query, &mut commands, &mut next_state);
+    }
+}
+
+type DockTarget = (
+    &'static Transform,
+    &'static ActionMenuTarget,
+    Option<&'static Velocity>,
+    Option<&'static Collider>,
+    Option<&'static PriceTable>,
+);
+
+fn dock_at(
+    target_entity: Entity,
+    target_query: &Query<DockTarget>,
+    commands: &mut Commands,
+    next_state: &mut NextState<crate::GameState>,
+) {
+    if let Ok((target_transform, target, velocity, collider, prices)) =
+        target_query.get(target_entity)
+    {
+        commands.insert_resource(DockedStation {
+            label: target.label.clone(),
+            position: target_transform.translation.truncate(),
+            radius: collider.map(Collider::radius).unwrap_or_default(),
+            velocity: velocity.map(|velocity| velocity.0).unwrap_or_default(),
+            prices: prices.copied(),
+        });
+    }
+    next_state.set(crate::GameState::Docked); // Switch to docked scene
+}
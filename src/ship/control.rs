@@ -0,0 +1,46 @@
+// Who flies a ship is kept apart from how it flies: pilots (the player, the AI, or
+// anything else) only write a `ShipControlIntent`, and the movement and weapons systems
+// act on it the same way for every ship.
+use crate::autopilot::Autopilot;
+use crate::controls::{Action, ActionState};
+use crate::game_over::Wrecked;
+use bevy::prelude::*;
+
+// Systems writing `ShipControlIntent`s run in this set, before the ships move and shoot
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ControlSet {
+    Intent,
+}
+
+// Tag component for the ship flown by the player
+#[derive(Component)]
+pub struct PlayerControlled;
+
+// What the pilot asks of the ship this frame
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq)]
+pub struct ShipControlIntent {
+    // From -1.0 (full right) to 1.0 (full left)
+    pub turn: f32,
+    // From -1.0 (brake) to 1.0 (full ahead)
+    pub thrust: f32,
+    pub fire: bool,
+}
+
+// Turns the player's bound keys and gamepad into the intent of their ship, unless the
+// autopilot is flying it
+#[allow(clippy::type_complexity)]
+pub fn player_ship_controls(
+    actions: Res<ActionState>,
+    mut ships: Query<
+        &mut ShipControlIntent,
+        (With<PlayerControlled>, Without<Autopilot>, Without<Wrecked>),
+    >,
+) {
+    for mut intent in &mut ships {
+        *intent = ShipControlIntent {
+            turn: actions.turn(),
+            thrust: actions.thrust(),
+            fire: actions.pressed(Action::Fire),
+        };
+    }
+}
@@ -0,0 +1,44 @@
+use crate::combat::damage::{DamageEvent, DamageSource, DamageType};
+use crate::physics::collision::{CollisionEvent, CollisionResponse};
+use crate::ship::spaceship::Spaceship;
+use bevy::prelude::*;
+
+// Impacts slower than this leave the hull untouched
+const SAFE_IMPACT_SPEED: f32 = 60.0;
+// Hull lost per unit of impact speed above the safe speed
+const HULL_DAMAGE_PER_SPEED: f32 = 1.0 / 800.0;
+
+// Damages ships running into celestial bodies, depending on how hard they hit and
+// on how the body responds to impacts
+pub fn impact_damage(
+    mut events: EventReader<CollisionEvent>,
+    mut ships: Query<&mut Spaceship>,
+    bodies: Query<&CollisionResponse>,
+    mut damage_events: EventWriter<DamageEvent>,
+) {
+    for event in events.read() {
+        let (Ok(mut ship), Ok(response)) = (ships.get_mut(event.entity), bodies.get(event.other))
+        else {
+            continue;
+        };
+        let damage = match *response {
+            // Nothing survives this, whatever the shields
+            CollisionResponse::Crash => f32::INFINITY,
+            CollisionResponse::Land { safe_speed } if event.impact_speed <= safe_speed => 0.0,
+            _ => (event.impact_speed - SAFE_IMPACT_SPEED).max(0.0) * HULL_DAMAGE_PER_SPEED,
+        };
+        if damage > 0.0 {
+            damage_events.write(DamageEvent {
+                target: event.entity,
+                source: Some(event.other),
+                source_kind: DamageSource::Body,
+                amount: damage,
+                kind: DamageType::Kinetic,
+            });
+        }
+        // Running into something kills the cruise speed of arcade flight
+        if event.impact_speed > 1.0 {
+            ship.throttle = 0.0;
+        }
+    }
+}
@@ -1,5 +1,8 @@
 // Player module
 pub mod action_menu;
+pub mod control;
+pub mod impact;
 pub mod movement;
 pub mod spaceship;
 pub mod ui;
+pub mod weapons;
@@ -1,45 +1,221 @@
+use crate::game_over::Wrecked;
+use crate::physics::Velocity;
+use crate::ship::control::ShipControlIntent;
 use crate::ship::spaceship::Spaceship;
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
-pub fn move_spaceship(
-    keyboard_input: Res<ButtonInput<KeyCode>>,
-    mut query: Query<(&mut Transform, &mut Spaceship)>,
+// Fuel burnt per second for each unit of acceleration the engines apply
+pub const FUEL_PER_THRUST: f32 = 0.0005;
+// How quickly arcade flight pulls the ship's velocity back onto its heading. External
+// forces such as gravity still make it drift, more so the stronger they are.
+const ARCADE_GRIP: f32 = 8.0;
+
+// How the ship reacts to the engines, chosen when starting a new game
+#[derive(
+    Resource, Debug, Component, PartialEq, Eq, Clone, Copy, Default, Serialize, Deserialize,
+)]
+pub enum FlightModel {
+    // The ship flies where it points, at the speed set by the throttle
+    #[default]
+    Arcade,
+    // Thrust accelerates the ship and momentum is kept when turning
+    Newtonian,
+}
+
+// Whether "down" fires retro thrusters against the ship's motion in newtonian flight.
+// Without them the ship can only slow down by turning around and burning.
+#[derive(Resource, Debug, Component, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub struct RetroThrust(pub bool);
+
+impl Default for RetroThrust {
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
+// Tuning shared by both flight models
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct FlightSettings {
+    // Top speed in world units per second
+    pub max_speed: f32,
+    // Acceleration of the main engine in world units per second squared
+    pub thrust: f32,
+    // Rotation speed in radians per second
+    pub rotation_speed: f32,
+    // Fraction of the velocity lost every second in newtonian flight, 0.0 for none
+    pub drag: f32,
+}
+
+impl Default for FlightSettings {
+    fn default() -> Self {
+        Self {
+            max_speed: 1200.0,
+            thrust: 200.0,
+            rotation_speed: std::f32::consts::PI,
+            drag: 0.0,
+        }
+    }
+}
+
+// Flies every ship as its pilot intends. Ships without their own flight model use the
+// one chosen for the game.
+#[allow(clippy::type_complexity)]
+pub fn move_spaceships(
+    mut query: Query<
+        (
+            &ShipControlIntent,
+            &mut Transform,
+            &mut Spaceship,
+            &mut Velocity,
+            Option<&FlightModel>,
+            Option<&RetroThrust>,
+        ),
+        Without<Wrecked>,
+    >,
     time: Res<Time>,
+    flight_model: Res<FlightModel>,
+    retro_thrust: Res<RetroThrust>,
+    settings: Res<FlightSettings>,
 ) {
-    let (mut transform, mut ship) = query.single_mut().unwrap();
-    let mut rotation_delta = 0.0;
-    let mut speed_delta = 0.0;
-    let mut burning_fuel = false;
-    if keyboard_input.pressed(KeyCode::ArrowLeft) {
-        rotation_delta += 1.0;
-    }
-    if keyboard_input.pressed(KeyCode::ArrowRight) {
-        rotation_delta -= 1.0;
+    for (intent, mut transform, mut ship, mut velocity, ship_model, ship_retro) in &mut query {
+        fly_ship(
+            &mut transform,
+            &mut ship,
+            &mut velocity,
+            intent.turn,
+            intent.thrust,
+            ship_model.copied().unwrap_or(*flight_model),
+            ship_retro.copied().unwrap_or(*retro_thrust),
+            &settings,
+            time.delta_secs(),
+        );
     }
-    // Only allow speed increase if ArrowUp is pressed and fuel > 0
-    if keyboard_input.pressed(KeyCode::ArrowUp) && ship.fuel > 0.0 {
-        speed_delta += 1.0;
-        burning_fuel = true;
+}
+
+// Applies one frame of pilot input to a ship. `turn` goes from -1.0
+// (full right) to 1.0 (full left), `thrust` from -1.0 (brake) to 1.0 (full ahead).
+#[allow(clippy::too_many_arguments)]
+fn fly_ship(
+    transform: &mut Transform,
+    ship: &mut Spaceship,
+    velocity: &mut Velocity,
+    turn: f32,
+    thrust: f32,
+    flight_model: FlightModel,
+    retro_thrust: RetroThrust,
+    settings: &FlightSettings,
+    dt: f32,
+) {
+    let turn = turn.clamp(-1.0, 1.0);
+    let has_fuel = ship.fuel > 0.0;
+    let accelerate = thrust > 0.0 && has_fuel;
+    let brake = thrust < 0.0;
+    let engine = thrust.abs().min(1.0) * settings.thrust;
+    // Update rotation (tilt)
+    transform.rotation *= Quat::from_rotation_z(turn * settings.rotation_speed * dt);
+    let forward = (transform.rotation * Vec3::Y).truncate();
+
+    // Acceleration actually applied by the engines this frame, used for the fuel burn
+    let mut burn = 0.0;
+    match flight_model {
+        FlightModel::Arcade => {
+            // Throttle is the cruise speed, braking is free
+            if accelerate {
+                ship.throttle += engine * dt;
+                burn = engine;
+            } else if brake {
+                ship.throttle = (ship.throttle - engine * dt).max(0.0);
+            }
+            ship.throttle = ship.throttle.min(settings.max_speed);
+            let target = forward * ship.throttle;
+            velocity.0 = velocity.lerp(target, (ARCADE_GRIP * dt).min(1.0));
+        }
+        FlightModel::Newtonian => {
+            if accelerate {
+                velocity.0 += forward * engine * dt;
+                burn = engine;
+            }
+            if brake && retro_thrust.0 && has_fuel {
+                let speed = velocity.length();
+                let delta = (engine * dt).min(speed);
+                let direction = velocity.normalize_or_zero();
+                velocity.0 -= direction * delta;
+                if delta > 0.0 {
+                    burn += engine;
+                }
+            }
+            velocity.0 *= (1.0 - settings.drag * dt).max(0.0);
+            velocity.0 = velocity.0.clamp_length_max(settings.max_speed);
+            ship.throttle = velocity.length();
+        }
     }
-    if keyboard_input.pressed(KeyCode::ArrowDown) {
-        speed_delta -= 1.0;
+    ship.fuel = (ship.fuel - FUEL_PER_THRUST * burn * dt).max(0.0);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DT: f32 = 1.0 / 60.0;
+
+    fn ship() -> Spaceship {
+        Spaceship {
+            throttle: 0.0,
+            fuel: 100.0,
+            hull: 100.0,
+            shields: 100.0,
+            weapons: 1,
+        }
     }
-    // Update rotation (tilt)
-    let rotation_speed = std::f32::consts::PI; // radians/sec
-    transform.rotation = transform.rotation
-        * Quat::from_rotation_z(rotation_delta * rotation_speed * time.delta_secs());
-    // Update speed (no upper limit)
-    if speed_delta > 0.0 && ship.fuel > 0.0 {
-        ship.throttle += speed_delta * 200.0 * time.delta_secs();
-    } else if speed_delta < 0.0 {
-        ship.throttle = (ship.throttle + speed_delta * 200.0 * time.delta_secs()).max(0.0);
+
+    #[test]
+    fn arcade_flight_pulls_the_drift_onto_the_heading() {
+        let settings = FlightSettings::default();
+        let mut transform = Transform::default();
+        let mut ship = ship();
+        ship.throttle = 300.0;
+        // Drifting sideways while pointing up
+        let mut velocity = Velocity(Vec2::new(300.0, 0.0));
+        for _ in 0..60 {
+            fly_ship(
+                &mut transform,
+                &mut ship,
+                &mut velocity,
+                0.0,
+                0.0,
+                FlightModel::Arcade,
+                RetroThrust::default(),
+                &settings,
+                DT,
+            );
+        }
+        assert!(velocity.distance(Vec2::new(0.0, 300.0)) < 1.0);
     }
-    // Burn fuel if accelerating (ArrowUp and fuel > 0)
-    if burning_fuel && ship.throttle > 0.0 {
-        let burn = 0.25 * ship.throttle / 800.0 * time.delta_secs();
-        ship.fuel = (ship.fuel - burn).max(0.0);
+
+    #[test]
+    fn newtonian_flight_keeps_momentum_when_turning() {
+        let settings = FlightSettings::default();
+        let mut transform = Transform::default();
+        let mut ship = ship();
+        let mut velocity = Velocity(Vec2::new(0.0, 300.0));
+        // Half a turn without thrust leaves the ship sliding backwards
+        for _ in 0..60 {
+            fly_ship(
+                &mut transform,
+                &mut ship,
+                &mut velocity,
+                1.0,
+                0.0,
+                FlightModel::Newtonian,
+                RetroThrust::default(),
+                &settings,
+                DT,
+            );
+        }
+        let forward = (transform.rotation * Vec3::Y).truncate();
+        assert!(forward.distance(Vec2::NEG_Y) < 1e-3);
+        assert_eq!(velocity.0, Vec2::new(0.0, 300.0));
+        assert_eq!(ship.fuel, 100.0);
     }
-    // Move in the direction the spaceship is facing
-    let forward = transform.rotation * Vec3::Y;
-    transform.translation += forward * ship.throttle * time.delta_secs();
 }
@@ -1,48 +1,154 @@
-use crate::ship::spaceship::Spaceship;
-use crate::space::SunDamageWarning;
+use crate::ship::control::PlayerControlled;
+use crate::ship::spaceship::{MAX_FUEL, Spaceship};
+use crate::ship::weapons::Weapon;
+use crate::space::{OnSpaceScreen, SunDamageWarning};
 use bevy::color::palettes::css::{DARK_CYAN, DARK_GRAY, YELLOW};
+use bevy::ecs::spawn::SpawnIter;
 use bevy::prelude::*;
 
+const BAR_WIDTH: f32 = 200.0;
+const BAR_HEIGHT: f32 = 24.0;
+const BAR_LABEL_WIDTH: f32 = 80.0;
+const PANEL_GAP: f32 = 8.0;
+
 #[derive(Component)]
 pub struct SidePanelRoot;
 
-pub fn spaceship_ui_panel(
-    q: Query<&Spaceship>,
-    mut commands: Commands,
-    root_query: Query<Entity, With<SidePanelRoot>>,
-    asset_server: Res<AssetServer>,
-    sun_damage_warning: Res<SunDamageWarning>,
-) {
-    if let Ok(root) = root_query.single() {
-        commands.entity(root).despawn();
+// Tag component for the speed readout of the side panel
+#[derive(Component)]
+pub struct SpeedReadout;
+
+// Which stat of the player's ship a bar of the side panel shows
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShipStat {
+    Fuel,
+    Hull,
+    Shields,
+    Weapons,
+    Heat,
+}
+
+impl ShipStat {
+    const ALL: [ShipStat; 5] = [
+        ShipStat::Fuel,
+        ShipStat::Hull,
+        ShipStat::Shields,
+        ShipStat::Weapons,
+        ShipStat::Heat,
+    ];
+
+    fn bar(self) -> HudBar {
+        match self {
+            ShipStat::Fuel => HudBar::new("Fuel", YELLOW.into()),
+            ShipStat::Hull => HudBar::new("Hull", DARK_CYAN.into()),
+            ShipStat::Shields => HudBar::new("Shields", DARK_GRAY.into()),
+            ShipStat::Weapons => HudBar::new("Weapons", Color::hsl(0.0, 0.75, 0.5)),
+            ShipStat::Heat => HudBar::new("Heat", Color::hsl(30.0, 0.9, 0.5)),
+        }
     }
-    let ship = q.single().unwrap();
-    let bar_width = 200.0;
-    let bar_height = 24.0;
-    let margin = 8.0;
-    let font_size = 18.0;
-    let text_font = TextFont {
-        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
-        ..default()
-    };
-    commands
-        .spawn((
+}
+
+// A labelled gauge filled from 0.0 to 1.0, with a "!" after the label when `warning` is
+// set. Systems only change the component, `render_hud_bars` then updates the label and
+// the fill of the bars that changed.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct HudBar {
+    pub label: &'static str,
+    pub color: Color,
+    pub value: f32,
+    pub warning: bool,
+}
+
+// Parts of a `HudBar`, found again by `render_hud_bars`
+#[derive(Component)]
+pub struct HudBarLabel;
+
+#[derive(Component)]
+pub struct HudBarTrack;
+
+#[derive(Component)]
+pub struct HudBarFill;
+
+impl HudBar {
+    pub fn new(label: &'static str, color: Color) -> Self {
+        Self {
+            label,
+            color,
+            value: 0.0,
+            warning: false,
+        }
+    }
+
+    // The bar's node tree, to be spawned as a child of a HUD panel
+    pub fn bundle(self, text_font: TextFont) -> impl Bundle {
+        (
             Node {
-                position_type: PositionType::Absolute,
-                right: Val::Px(24.0),
-                top: Val::Px(24.0),
+                width: Val::Px(BAR_WIDTH),
+                height: Val::Px(BAR_HEIGHT),
                 flex_direction: FlexDirection::Row,
-                align_items: AlignItems::FlexEnd,
-                row_gap: Val::Px(margin),
+                align_items: AlignItems::Center,
                 ..default()
             },
-            BackgroundColor(Color::WHITE.with_alpha(0.1)),
-            SidePanelRoot,
-        ))
-        .with_children(|parent: &mut bevy::ecs::hierarchy::ChildSpawnerCommands| {
+            children![
+                (
+                    Text::default(),
+                    text_font,
+                    Node {
+                        width: Val::Px(BAR_LABEL_WIDTH),
+                        height: Val::Px(BAR_HEIGHT),
+                        ..default()
+                    },
+                    TextColor(Color::BLACK),
+                    HudBarLabel,
+                ),
+                (
+                    Node {
+                        width: Val::Px(BAR_WIDTH - BAR_LABEL_WIDTH - 10.0),
+                        height: Val::Px(BAR_HEIGHT - 8.0),
+                        margin: UiRect::left(Val::Px(8.0)),
+                        ..default()
+                    },
+                    HudBarTrack,
+                    children![(
+                        Node {
+                            width: Val::Percent(0.0),
+                            height: Val::Percent(100.0),
+                            ..default()
+                        },
+                        BackgroundColor(self.color),
+                        HudBarFill,
+                    )],
+                ),
+            ],
+            self,
+        )
+    }
+}
+
+// Builds the side panel once when entering space, the update systems below keep it
+// current
+pub fn spawn_ship_hud(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let text_font = TextFont {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        ..default()
+    };
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            right: Val::Px(24.0),
+            top: Val::Px(24.0),
+            flex_direction: FlexDirection::Row,
+            align_items: AlignItems::FlexEnd,
+            row_gap: Val::Px(PANEL_GAP),
+            ..default()
+        },
+        BackgroundColor(Color::WHITE.with_alpha(0.1)),
+        SidePanelRoot,
+        OnSpaceScreen,
+        children![
             // Speed indicator only (no throttle bar)
-            parent
-                .spawn((Node {
+            (
+                Node {
                     width: Val::Px(180.0),
                     height: Val::Px(50.0),
                     flex_direction: FlexDirection::ColumnReverse,
@@ -50,130 +156,100 @@ pub fn spaceship_ui_panel(
                     justify_content: JustifyContent::FlexEnd,
                     margin: UiRect::right(Val::Px(16.0)),
                     ..default()
-                },))
-                .with_children(|col| {
-                    // Speed indicator
-                    col.spawn((
-                        Text::new(format!("speed: {:.0}", ship.throttle)),
-                        text_font.clone(),
-                        Node {
-                            margin: UiRect::top(Val::Px(8.0)),
-                            ..default()
-                        },
-                        TextColor(Color::BLACK.into()),
-                    ));
-                });
+                },
+                children![(
+                    Text::default(),
+                    text_font.clone(),
+                    Node {
+                        margin: UiRect::top(Val::Px(8.0)),
+                        ..default()
+                    },
+                    TextColor(Color::BLACK),
+                    SpeedReadout,
+                )],
+            ),
             // Stats panel
-            parent
-                .spawn((Node {
+            (
+                Node {
                     flex_direction: FlexDirection::Column,
                     align_items: AlignItems::FlexEnd,
-                    row_gap: Val::Px(margin),
+                    row_gap: Val::Px(PANEL_GAP),
                     ..default()
-                },))
-                .with_children(|panel| {
-                    spawn_bar(
-                        panel,
-                        "Fuel",
-                        ship.fuel,
-                        YELLOW.into(),
-                        bar_width,
-                        bar_height,
-                        font_size,
-                        &text_font,
-                        None,
-                    );
-                    spawn_bar(
-                        panel,
-                        "Hull",
-                        ship.hull,
-                        DARK_CYAN.into(),
-                        bar_width,
-                        bar_height,
-                        font_size,
-                        &text_font,
-                        if sun_damage_warning.0 {
-                            Some("!")
-                        } else {
-                            None
-                        },
-                    );
-                    spawn_bar(
-                        panel,
-                        "Shields",
-                        ship.shields,
-                        DARK_GRAY.into(),
-                        bar_width,
-                        bar_height,
-                        font_size,
-                        &text_font,
-                        None,
-                    );
-                    spawn_bar(
-                        panel,
-                        "Weapons",
-                        ship.weapons as f32 / 10.0,
-                        Color::hsl(0.0, 0.75, 0.5),
-                        bar_width,
-                        bar_height,
-                        font_size,
-                        &text_font,
-                        None,
-                    );
-                });
-        });
+                },
+                Children::spawn(SpawnIter(
+                    ShipStat::ALL
+                        .into_iter()
+                        .map(move |stat| (stat.bar().bundle(text_font.clone()), stat)),
+                )),
+            ),
+        ],
+    ));
 }
 
-pub fn spawn_bar(
-    parent: &mut bevy::ecs::hierarchy::ChildSpawnerCommands,
-    label: &str,
-    value: f32,
-    color: Color,
-    width: f32,
-    height: f32,
-    _font_size: f32, // remove unused warning
-    text_font: &TextFont,
-    warning: Option<&str>,
+// Copies the stats of the player's ship into the side panel when they change
+#[allow(clippy::type_complexity)]
+pub fn update_ship_hud(
+    ships: Query<(Ref<Spaceship>, Option<Ref<Weapon>>), With<PlayerControlled>>,
+    sun_damage_warning: Res<SunDamageWarning>,
+    mut bars: Query<(&ShipStat, &mut HudBar)>,
+    mut speed_readout: Query<&mut Text, With<SpeedReadout>>,
 ) {
-    parent
-        .spawn((Node {
-            width: Val::Px(width),
-            height: Val::Px(height),
-            flex_direction: FlexDirection::Row,
-            align_items: AlignItems::Center,
-            ..default()
-        },))
-        .with_children(|bar| {
-            let label_text = if let Some(w) = warning {
-                format!("{} {}", label, w)
-            } else {
-                label.to_string()
-            };
-            bar.spawn((
-                Text::new(label_text),
-                text_font.clone(),
-                Node {
-                    width: Val::Px(80.0),
-                    height: Val::Px(height),
-                    ..default()
-                },
-                TextColor(Color::BLACK.into()),
-            ));
-            bar.spawn((Node {
-                width: Val::Px(width - 90.0),
-                height: Val::Px(height - 8.0),
-                margin: UiRect::left(Val::Px(8.0)),
-                ..default()
-            },))
-                .with_children(|bar_bg| {
-                    bar_bg.spawn((
-                        Node {
-                            width: Val::Px((width - 90.0) * value.clamp(0.0, 1.0)),
-                            height: Val::Px(height - 8.0),
-                            ..default()
-                        },
-                        BackgroundColor(color),
-                    ));
-                });
+    // Nothing to show while the player has no ship
+    let Ok((ship, weapon)) = ships.single() else {
+        return;
+    };
+    let weapon_changed = weapon.as_ref().is_some_and(|weapon| weapon.is_changed());
+    if !ship.is_changed() && !weapon_changed && !sun_damage_warning.is_changed() {
+        return;
+    }
+    let heat = weapon
+        .as_ref()
+        .map(|weapon| weapon.heat)
+        .unwrap_or_default();
+    let overheated = weapon.as_ref().is_some_and(|weapon| weapon.overheated);
+    for (stat, mut bar) in &mut bars {
+        let (value, warning) = match stat {
+            ShipStat::Fuel => (ship.fuel / MAX_FUEL, false),
+            ShipStat::Hull => (ship.hull, sun_damage_warning.0),
+            ShipStat::Shields => (ship.shields, false),
+            ShipStat::Weapons => (ship.weapons as f32 / 10.0, false),
+            ShipStat::Heat => (heat, overheated),
+        };
+        bar.set_if_neq(HudBar {
+            value,
+            warning,
+            ..*bar
         });
+    }
+    if let Ok(mut text) = speed_readout.single_mut() {
+        let speed = format!("speed: {:.0}", ship.throttle);
+        if text.0 != speed {
+            text.0 = speed;
+        }
+    }
+}
+
+// Redraws the label and the fill of every bar whose value changed
+pub fn render_hud_bars(
+    bars: Query<(&HudBar, &Children), Changed<HudBar>>,
+    mut labels: Query<&mut Text, With<HudBarLabel>>,
+    tracks: Query<&Children, With<HudBarTrack>>,
+    mut fills: Query<&mut Node, With<HudBarFill>>,
+) {
+    for (bar, children) in &bars {
+        for child in children {
+            if let Ok(mut text) = labels.get_mut(*child) {
+                text.0 = if bar.warning {
+                    format!("{} !", bar.label)
+                } else {
+                    bar.label.to_string()
+                };
+            }
+            for fill in tracks.get(*child).into_iter().flatten() {
+                if let Ok(mut node) = fills.get_mut(*fill) {
+                    node.width = Val::Percent(bar.value.clamp(0.0, 1.0) * 100.0);
+                }
+            }
+        }
+    }
 }
@@ -0,0 +1,120 @@
+use crate::combat::projectile::spawn_projectile;
+use crate::game_over::Wrecked;
+use crate::physics::Velocity;
+use crate::ship::control::ShipControlIntent;
+use crate::ship::spaceship::Spaceship;
+use bevy::prelude::*;
+
+// Seconds between two shots
+const FIRE_INTERVAL: f32 = 0.15;
+// Muzzle speed added to the ship's own velocity
+pub const PROJECTILE_SPEED: f32 = 900.0;
+const PROJECTILE_LIFETIME: f32 = 1.5;
+// Damage of a single shot for each weapons level
+const DAMAGE_PER_LEVEL: f32 = 1.0;
+// Heat added by a shot and lost per second, from 0.0 (cold) to 1.0 (overheated)
+const HEAT_PER_SHOT: f32 = 0.08;
+const COOLING_RATE: f32 = 0.3;
+// Once overheated, the gun only fires again after cooling down below this level
+const OVERHEAT_RECOVERY: f32 = 0.4;
+// Distance in front of the ship's center where shots appear
+const MUZZLE_OFFSET: f32 = 60.0;
+
+// The ship's gun, its power comes from `Spaceship::weapons`
+#[derive(Component, Debug, Clone)]
+pub struct Weapon {
+    pub cooldown: Timer,
+    pub heat: f32,
+    pub overheated: bool,
+}
+
+impl Weapon {
+    // A gun firing at most once every `interval` seconds
+    pub fn with_interval(interval: f32) -> Self {
+        Self {
+            cooldown: Timer::from_seconds(interval, TimerMode::Once),
+            heat: 0.0,
+            overheated: false,
+        }
+    }
+}
+
+impl Default for Weapon {
+    fn default() -> Self {
+        Self::with_interval(FIRE_INTERVAL)
+    }
+}
+
+// Guns cool down on every ship, whoever flies it
+pub fn cool_weapons(mut weapons: Query<&mut Weapon>, time: Res<Time>) {
+    let dt = time.delta_secs();
+    for mut weapon in &mut weapons {
+        weapon.cooldown.tick(time.delta());
+        weapon.heat = (weapon.heat - COOLING_RATE * dt).max(0.0);
+        if weapon.overheated && weapon.heat < OVERHEAT_RECOVERY {
+            weapon.overheated = false;
+        }
+    }
+}
+
+// Fires the guns of the ships whose pilot wants to
+#[allow(clippy::type_complexity)]
+pub fn fire_weapons(
+    mut commands: Commands,
+    mut ships: Query<
+        (
+            Entity,
+            &ShipControlIntent,
+            &Transform,
+            &Velocity,
+            &Spaceship,
+            &mut Weapon,
+        ),
+        Without<Wrecked>,
+    >,
+) {
+    for (entity, intent, transform, velocity, ship, mut weapon) in &mut ships {
+        if intent.fire {
+            fire(
+                &mut commands,
+                entity,
+                transform,
+                velocity,
+                ship,
+                &mut weapon,
+            );
+        }
+    }
+}
+
+// Shoots once if the gun is ready
+fn fire(
+    commands: &mut Commands,
+    entity: Entity,
+    transform: &Transform,
+    velocity: &Velocity,
+    ship: &Spaceship,
+    weapon: &mut Weapon,
+) {
+    let can_fire = weapon.cooldown.finished() && !weapon.overheated && ship.weapons > 0;
+    if !can_fire {
+        return;
+    }
+    let forward = (transform.rotation * Vec3::Y).truncate();
+    let muzzle = transform.translation.truncate() + forward * MUZZLE_OFFSET;
+    spawn_projectile(
+        commands,
+        entity,
+        Transform::from_translation(muzzle.extend(transform.translation.z))
+            .with_rotation(transform.rotation),
+        velocity.0 + forward * PROJECTILE_SPEED,
+        DAMAGE_PER_LEVEL * ship.weapons as f32,
+        PROJECTILE_LIFETIME,
+    );
+    weapon.cooldown.reset();
+    weapon.heat += HEAT_PER_SHOT;
+    if weapon.heat >= 1.0 {
+        weapon.heat = 1.0;
+        weapon.overheated = true;
+    }
+}
@@ -1,24 +1,47 @@
-use crate::GameState;
-use crate::planets::base::{Base, spawn_base};
-use crate::planets::moon::{Moon, spawn_moon};
+use crate::ai::{pirate_patrol, spawn_pirate};
+use crate::camera::move_camera;
+use crate::combat::damage::{
+    DamageEvent, DamageSource, DamageType, ShieldRecharge, ShieldSettings,
+};
+use crate::combat::{CombatSet, Health, Objective};
+use crate::defense::{BASE_HEALTH, GameMode};
+use crate::docked::DockedStation;
+use crate::economy::PriceTable;
+use crate::nav::NavPoint;
+use crate::pause::PauseState;
+use crate::physics::collision::{Collider, CollisionResponse};
+use crate::physics::gravity::GravitySource;
+use crate::physics::{PhysicsSet, Velocity};
+use crate::planets::base::{base_orbit, spawn_base};
+use crate::planets::moon::{MOON_START_OFFSET, moon_orbit, spawn_moon};
+use crate::planets::orbit::{Orbit, advance_orbits, place_orbiting_bodies};
 use crate::planets::sun::{Sun, rotate_sun, spawn_sun};
+use crate::quality::ui_refresh_due;
+use crate::radar::RadarSignature;
+use crate::session::{GameSession, save_session};
 use crate::ship::action_menu::{
-    ActionMenuTarget, action_menu_button_system, show_action_menu_system,
+    ActionMenuTarget, action_menu_button_system, interact_to_dock, show_action_menu_system,
 };
-use crate::ship::movement::move_spaceship;
+use crate::ship::control::{ControlSet, PlayerControlled, player_ship_controls};
+use crate::ship::impact::impact_damage;
+use crate::ship::movement::{FlightModel, FlightSettings, RetroThrust, move_spaceships};
 use crate::ship::spaceship::{Spaceship, spawn_spaceship};
-use crate::ship::ui::spaceship_ui_panel;
+use crate::ship::ui::{render_hud_bars, spawn_ship_hud, update_ship_hud};
+use crate::ship::weapons::{Weapon, cool_weapons, fire_weapons};
+use crate::{DisplayQuality, GameState};
 use bevy::prelude::*;
 use rand::Rng;
 
-const STAR_LAYERS: usize = 3;
-const STARS_PER_LAYER: usize = 100;
+// Layers from the farthest to the nearest. Lower display qualities drop the far ones,
+// the quality profile says how many are kept and how many stars each one has.
+const STAR_LAYERS: usize = 4;
 const STAR_COLORS: [Color; STAR_LAYERS] = [
     Color::BLACK,
+    Color::srgb(0.1, 0.1, 0.1),
     Color::srgb(0.2, 0.2, 0.2),
     Color::srgb(0.5, 0.5, 0.5),
 ];
-const STAR_PARALLAX: [f32; STAR_LAYERS] = [0.2, 0.5, 0.8];
+const STAR_PARALLAX: [f32; STAR_LAYERS] = [0.1, 0.2, 0.5, 0.8];
 
 #[derive(Component)]
 struct Star {
@@ -26,64 +49,213 @@ struct Star {
     base_pos: Vec2,
 }
 
+// Tag component used to tag every entity spawned for the space screen
 #[derive(Component)]
-struct OnSpaceScreen;
+pub struct OnSpaceScreen;
+
+// How far from the station's surface the ship is placed when undocking, just outside
+// the range where the action menu pops up
+const UNDOCK_DISTANCE: f32 = 140.0;
+// Where a new game starts: behind the Base along its orbit and clear of its surface, so
+// the Base pulls away from the waiting ship instead of running it over
+const SHIP_START: Vec3 = Vec3::new(-1800.0, 1800.0, 0.0);
+// How far from the Sun's center its heat damages the hull
+pub const SUN_DAMAGE_RADIUS: f32 = 600.0;
 
 pub fn space_plugin(app: &mut App) {
-    app.add_systems(OnEnter(GameState::Space), setup_space)
+    app.init_resource::<FlightModel>()
+        .init_resource::<RetroThrust>()
+        .init_resource::<FlightSettings>()
+        .add_systems(OnEnter(GameState::Space), (setup_space, spawn_ship_hud))
         .add_systems(
             Update,
             (
-                parallax_starfield,
-                move_spaceship,
-                camera_follow_and_zoom.after(parallax_starfield), // <-- add .after here
-                spaceship_ui_panel,
-                refuel_on_base_visit,
+                respawn_starfield
+                    .before(parallax_starfield)
+                    .run_if(resource_changed::<DisplayQuality>),
+                parallax_starfield.after(move_camera),
+                player_ship_controls.in_set(ControlSet::Intent),
+                move_spaceships
+                    .after(ControlSet::Intent)
+                    .before(PhysicsSet::Forces),
+                cool_weapons,
+                fire_weapons.after(move_spaceships).after(cool_weapons),
+                (update_ship_hud, render_hud_bars)
+                    .chain()
+                    .run_if(ui_refresh_due),
                 rotate_sun,
-                sun_proximity_damage,
-                show_action_menu_system,
+                (advance_orbits, place_orbiting_bodies)
+                    .chain()
+                    .before(PhysicsSet::Forces),
+                sun_proximity_damage.before(CombatSet::ApplyDamage),
+                show_action_menu_system.after(PhysicsSet::Collide),
+                impact_damage
+                    .after(PhysicsSet::Collide)
+                    .before(CombatSet::ApplyDamage),
                 action_menu_button_system,
+                interact_to_dock,
             )
-                .run_if(in_state(GameState::Space)),
+                .run_if(in_state(PauseState::Running)),
         )
-        .add_systems(OnExit(GameState::Space), despawn_space_entities);
+        .add_systems(
+            OnExit(GameState::Space),
+            (save_session, despawn_space_entities).chain(),
+        );
 }
 
+#[allow(clippy::too_many_arguments)]
 fn setup_space(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
     asset_server: Res<AssetServer>,
     atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    docked_station: Option<Res<DockedStation>>,
+    session: Res<GameSession>,
+    shield_settings: Res<ShieldSettings>,
+    game_mode: Res<GameMode>,
+    display_quality: Res<DisplayQuality>,
 ) {
-    spawn_starfield(&mut commands);
+    spawn_starfield(&mut commands, *display_quality);
 
-    // Spaceship
-    spawn_spaceship(&mut commands, &asset_server, &mut meshes, &mut materials);
-    // Base (earth sprite)
+    // Spaceship, restored from the session if we are coming back to space
+    let ship_entity = spawn_spaceship(
+        &mut commands,
+        &asset_server,
+        &mut meshes,
+        &mut materials,
+        SHIP_START,
+    );
+    commands.entity(ship_entity).insert((
+        Collider::aabb(0.8),
+        Weapon::default(),
+        ShieldRecharge::new(shield_settings.regen_delay),
+        PlayerControlled,
+        OnSpaceScreen,
+    ));
+    if let Some(snapshot) = &session.ship {
+        let mut stats = snapshot.stats.clone();
+        let mut transform = snapshot.transform;
+        let mut velocity = snapshot.velocity;
+        // Coming back from being docked: place the ship next to the station, moving with it
+        if let Some(station) = &docked_station {
+            let direction = (transform.translation.truncate() - station.position)
+                .try_normalize()
+                .unwrap_or(Vec2::NEG_Y);
+            transform.translation = (station.position
+                + direction * (station.radius + UNDOCK_DISTANCE))
+                .extend(transform.translation.z);
+            stats.throttle = 0.0;
+            velocity = Velocity(station.velocity);
+        }
+        commands
+            .entity(ship_entity)
+            .insert((stats, transform, velocity));
+    }
+    if docked_station.is_some() {
+        commands.remove_resource::<DockedStation>();
+    }
+    // Sun (sun sprite)
+    let sun_entity = spawn_sun(&mut commands, &asset_server);
+    commands.entity(sun_entity).insert((
+        GravitySource::SUN,
+        Collider::circle(0.83),
+        CollisionResponse::Crash,
+        RadarSignature::Sun,
+        OnSpaceScreen,
+    ));
+    // Base (earth sprite), orbiting the Sun
     let base_entity = spawn_base(&mut commands, &asset_server, atlas_layouts);
-    commands.entity(base_entity).insert(ActionMenuTarget {
-        label: "Base".to_string(),
-    });
-    // Moon (moon sprite)
+    commands.entity(base_entity).insert((
+        ActionMenuTarget {
+            label: "Base".to_string(),
+        },
+        NavPoint {
+            label: "Base".to_string(),
+        },
+        PriceTable::BASE,
+        GravitySource::BASE,
+        Collider::circle(0.9),
+        CollisionResponse::Bounce { restitution: 0.5 },
+        base_orbit(sun_entity),
+        Velocity::default(),
+        RadarSignature::Station,
+        OnSpaceScreen,
+    ));
+    // In Z Defense the Base is what the raiders are after
+    if *game_mode == GameMode::ZDefense {
+        commands
+            .entity(base_entity)
+            .insert((Health::new(BASE_HEALTH), Objective));
+    }
+    // Moon (moon sprite), orbiting the Base
     let moon_entity = spawn_moon(&mut commands, &asset_server);
-    commands.entity(moon_entity).insert(ActionMenuTarget {
-        label: "Moon".to_string(),
+    commands.entity(moon_entity).insert((
+        ActionMenuTarget {
+            label: "Moon".to_string(),
+        },
+        NavPoint {
+            label: "Moon".to_string(),
+        },
+        PriceTable::MOON,
+        GravitySource::MOON,
+        Collider::circle(0.87),
+        CollisionResponse::Land { safe_speed: 150.0 },
+        moon_orbit(base_entity),
+        Velocity::default(),
+        RadarSignature::Station,
+        OnSpaceScreen,
+    ));
+
+    // Pirates patrolling around the Moon, without those already shot down
+    let pirates = session.pirates.clone().unwrap_or_else(|| {
+        let moon_position = session
+            .moon
+            .map(|moon| moon.transform.translation.truncate())
+            .unwrap_or(MOON_START_OFFSET);
+        pirate_patrol(moon_position)
     });
-    // Sun (sun sprite)
-    spawn_sun(&mut commands, &asset_server);
+    for pirate in pirates {
+        spawn_pirate(
+            &mut commands,
+            &asset_server,
+            moon_entity,
+            pirate,
+            shield_settings.regen_delay,
+        );
+    }
+
+    // Bodies keep the transform and orbital position they had when we left
+    for (entity, snapshot, orbit) in [
+        (sun_entity, session.sun, None),
+        (base_entity, session.base, Some(base_orbit(sun_entity))),
+        (moon_entity, session.moon, Some(moon_orbit(base_entity))),
+    ] {
+        let Some(snapshot) = snapshot else {
+            continue;
+        };
+        commands.entity(entity).insert(snapshot.transform);
+        if let Some(health) = snapshot.health {
+            commands.entity(entity).insert(health);
+        }
+        if let (Some(orbit), Some(phase)) = (orbit, snapshot.orbit_phase) {
+            commands.entity(entity).insert(Orbit { phase, ..orbit });
+        }
+    }
 }
 
-fn spawn_starfield(commands: &mut Commands) {
+fn spawn_starfield(commands: &mut Commands, quality: DisplayQuality) {
+    let profile = quality.profile();
     let mut rng = rand::rng();
-    for layer in 0..STAR_LAYERS {
-        for _ in 0..STARS_PER_LAYER {
+    let skipped_layers = STAR_LAYERS.saturating_sub(profile.star_layers);
+    for (layer, color) in STAR_COLORS.into_iter().enumerate().skip(skipped_layers) {
+        for _ in 0..profile.stars_per_layer {
             let x = rng.random_range(-2000.0..2000.0);
             let y = rng.random_range(-2000.0..2000.0);
             let size = rng.random_range(1.0..3.0) * (layer as f32 + 1.0);
             commands.spawn((
                 Sprite {
-                    color: STAR_COLORS[layer],
+                    color,
                     custom_size: Some(Vec2::splat(size)),
                     ..Default::default()
                 },
@@ -98,101 +270,76 @@ fn spawn_starfield(commands: &mut Commands) {
     }
 }
 
+// Rebuilds the starfield when the display quality changes while in space. The first
+// quality seen is the one `setup_space` just built it with.
+fn respawn_starfield(
+    mut commands: Commands,
+    stars: Query<Entity, With<Star>>,
+    display_quality: Res<DisplayQuality>,
+    mut applied: Local<Option<DisplayQuality>>,
+) {
+    if *applied == Some(*display_quality) {
+        return;
+    }
+    if applied.is_some() {
+        for entity in &stars {
+            commands.entity(entity).despawn();
+        }
+        spawn_starfield(&mut commands, *display_quality);
+    }
+    *applied = Some(*display_quality);
+}
+
+// Stars lag behind the camera, the farther layers more
+#[allow(clippy::type_complexity)]
 fn parallax_starfield(
     mut param_set: ParamSet<(
-        Query<&Transform, With<Spaceship>>,
+        Query<&Transform, With<Camera2d>>,
         Query<(&Star, &mut Transform)>,
     )>,
 ) {
-    if let Ok(player_transform) = param_set.p0().single() {
-        let player_pos = player_transform.translation.truncate();
+    if let Ok(camera_transform) = param_set.p0().single() {
+        let camera_pos = camera_transform.translation.truncate();
         for (star, mut transform) in param_set.p1().iter_mut() {
             let parallax = STAR_PARALLAX[star.layer];
-            let offset = player_pos * (1.0 - parallax);
+            let offset = camera_pos * (1.0 - parallax);
             transform.translation.x = star.base_pos.x + offset.x;
             transform.translation.y = star.base_pos.y + offset.y;
         }
     }
 }
 
-fn refuel_on_base_visit(
-    mut ship_query: Query<(&mut Spaceship, &Transform)>,
-    base_query: Query<&Transform, With<Base>>,
-    moon_query: Query<&Transform, With<Moon>>,
-) {
-    if let (Ok((mut ship, ship_transform)), Ok(base_transform), Ok(moon_transform)) = (
-        ship_query.single_mut(),
-        base_query.single(),
-        moon_query.single(),
-    ) {
-        let ship_pos = ship_transform.translation.truncate();
-        let base_pos = base_transform.translation.truncate();
-        let moon_pos = moon_transform.translation.truncate();
-        if ship_pos.distance(base_pos) < 150.0 || ship_pos.distance(moon_pos) < 100.0 {
-            ship.fuel = 1.0;
-        }
-    }
-}
-
-fn camera_follow_and_zoom(
-    mut param_set: ParamSet<(
-        Query<&Transform, With<Spaceship>>,
-        Query<&Transform, With<Base>>,
-        Query<&mut Transform, With<Camera2d>>,
-    )>,
-) {
-    // Get player position
-    let player_pos = if let Ok(player_transform) = param_set.p0().single() {
-        player_transform.translation.truncate()
-    } else {
-        return;
-    };
-
-    // Get base position
-    let base_pos = if let Ok(base_transform) = param_set.p1().single() {
-        base_transform.translation.truncate()
-    } else {
-        return;
-    };
-
-    let out_of_bounds = (player_pos - base_pos).length() > 400.0;
-    let target_zoom = if out_of_bounds { 6.0 } else { 2.0 };
-    let zoom_speed = 5.0;
-    for mut cam_transform in param_set.p2().iter_mut() {
-        let current_zoom = cam_transform.scale.x;
-        let new_zoom = current_zoom + (target_zoom - current_zoom) * zoom_speed * 0.016;
-        cam_transform.translation.x = player_pos.x;
-        cam_transform.translation.y = player_pos.y;
-        cam_transform.scale = Vec3::splat(new_zoom);
-    }
-}
-
+// The Sun's heat burns every ship that gets too close, the warning is the player's
 fn sun_proximity_damage(
-    mut ship_query: Query<(&mut Spaceship, &Transform)>,
-    sun_query: Query<&Transform, With<Sun>>,
+    ship_query: Query<(Entity, &Transform, Has<PlayerControlled>), With<Spaceship>>,
+    sun_query: Query<(Entity, &Transform), With<Sun>>,
     time: Res<Time>,
     sun_damage_warning: Option<ResMut<crate::space::SunDamageWarning>>,
+    mut damage_events: EventWriter<DamageEvent>,
 ) {
-    if let (Ok((mut ship, ship_transform)), Ok(sun_transform), Some(mut warning)) = (
-        ship_query.single_mut(),
-        sun_query.single(),
-        sun_damage_warning,
-    ) {
-        let ship_pos = ship_transform.translation.truncate();
+    if let (Ok((sun, sun_transform)), Some(mut warning)) = (sun_query.single(), sun_damage_warning)
+    {
         let sun_pos = sun_transform.translation.truncate();
-        let dist = ship_pos.distance(sun_pos);
-        let damage_radius = 600.0;
         let damage_per_sec = 0.25;
-        if dist < damage_radius {
-            ship.hull = (ship.hull - damage_per_sec * time.delta().as_secs_f32()).max(0.0);
-            warning.0 = true;
-        } else {
-            warning.0 = false;
+        let mut player_burning = false;
+        for (ship, ship_transform, is_player) in &ship_query {
+            let dist = ship_transform.translation.truncate().distance(sun_pos);
+            if dist < SUN_DAMAGE_RADIUS {
+                damage_events.write(DamageEvent {
+                    target: ship,
+                    source: Some(sun),
+                    source_kind: DamageSource::Body,
+                    amount: damage_per_sec * time.delta().as_secs_f32(),
+                    kind: DamageType::Solar,
+                });
+                player_burning |= is_player;
+            }
         }
+        warning.set_if_neq(SunDamageWarning(player_burning));
     }
 }
 
-#[derive(Resource, Default)]
+#[derive(Resource, Default, PartialEq)]
 pub struct SunDamageWarning(pub bool);
 
 fn despawn_space_entities(mut commands: Commands, q: Query<Entity, With<OnSpaceScreen>>) {
@@ -200,3 +347,37 @@ fn despawn_space_entities(mut commands: Commands, q: Query<Entity, With<OnSpaceS
         commands.entity(entity).despawn();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics::gravity::gravity_acceleration;
+    use crate::planets::sun::SUN_POSITION;
+    use std::f32::consts::TAU;
+
+    // Distance between the centers of the Base and the ship when they touch: the Base's
+    // collider is 1350 across its 500 px sprite at scale 6, the ship's about 50
+    const BASE_CONTACT_DISTANCE: f32 = 1400.0;
+
+    #[test]
+    fn a_new_game_starts_clear_of_the_base() {
+        // Drifting with the engines off is the worst case, arcade flight holds the ship
+        // still on top of it
+        let mut orbit = base_orbit(Entity::PLACEHOLDER);
+        let mut position = SHIP_START.truncate();
+        let mut velocity = Vec2::ZERO;
+        let dt = 1.0 / 60.0;
+        for frame in 0..60 * 10 {
+            orbit.phase += TAU / orbit.period * dt;
+            let base = SUN_POSITION + orbit.offset();
+            velocity += (gravity_acceleration(SUN_POSITION, &GravitySource::SUN, position)
+                + gravity_acceleration(base, &GravitySource::BASE, position))
+                * dt;
+            position += velocity * dt;
+            assert!(
+                position.distance(base) > BASE_CONTACT_DISTANCE,
+                "the Base reached the ship after {frame} frames"
+            );
+        }
+    }
+}
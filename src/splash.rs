@@ -1,4 +1,4 @@
-use bevy::{color::palettes::css::WHITE, prelude::*, ui::stack_z_offsets::NODE};
+use bevy::{color::palettes::css::WHITE, prelude::*};
 
 use super::{GameState, despawn_screen};
 